@@ -1,14 +1,29 @@
+use bounded_static::IntoBoundedStatic;
 use chrono::Utc;
 use cli_helpers::prelude::*;
 use meta_ads_access::{
     client::request::SearchType,
-    model::{Ad, Response},
+    library::headers::HeaderProfile,
+    library::index::{ArchiveIndex, SkipPolicy},
+    library::rate_limit::RateLimit,
+    library::request::{
+        ActiveStatus, AdType, MediaType, PageParams, SearchParams, SearchType as LibrarySearchType,
+    },
+    library::{Failure, FetchOptions},
+    model::{
+        Ad, Response,
+        country::CountryCode,
+        language::LanguageCode,
+        library::collation::{Collated, Collator},
+        library::versioned::{AdSummary, LibraryResponse},
+    },
     token::Creds,
     version::GraphApiVersion,
 };
 use scraper_trail::archive::entry::Entry;
+use scraper_trail::exchange::Exchange;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -36,8 +51,12 @@ pub enum Error {
     TomlDe(#[from] toml::de::Error),
     #[error("TOML serialization error")]
     TomlSer(#[from] toml::ser::Error),
+    #[error("Library index error")]
+    LibraryIndex(#[from] meta_ads_access::library::index::Error),
     #[error("Invalid ID line")]
     InvalidIdLine(String),
+    #[error("Failed to download {failed} of {count} ads")]
+    FailedDownloads { failed: usize, count: usize },
 }
 
 #[tokio::main]
@@ -51,20 +70,32 @@ async fn main() -> Result<(), Error> {
             version,
             terms,
             country,
+            language,
             exact,
             after,
             output,
             limit,
             full,
             full_output,
+            cookies,
+            profile,
+            proxies,
+            user_agent,
             delay,
+            reach_columns,
+            fetch,
         } => {
             let creds: Creds = toml::from_str(&std::fs::read_to_string(creds)?)?;
             log_token_status(creds.status(Utc::now()));
 
             let client = meta_ads_access::client::Client::new(creds.token, output.as_deref());
-            let library_client =
-                meta_ads_access::library::Client::new::<_, String>(full_output.as_deref(), None)?;
+            let library_client = fetch.apply(library_client(
+                full_output.clone(),
+                cookies,
+                &profile,
+                proxies,
+                user_agent,
+            )?);
 
             let search_type = if exact {
                 SearchType::KeywordExactPhrase
@@ -78,6 +109,7 @@ async fn main() -> Result<(), Error> {
                     terms: &terms,
                     countries: &country,
                     search_type,
+                    languages: &language,
                     after: after.as_deref(),
                     limit,
                     delay: std::time::Duration::from_secs(delay),
@@ -88,19 +120,14 @@ async fn main() -> Result<(), Error> {
                 .has_headers(false)
                 .from_writer(std::io::stdout());
 
+            let mut ids = vec![];
+
             for result in results {
                 match result.result() {
                     Ok(ads) => {
                         for ad in ads {
-                            writer.write_record([
-                                ad.id.to_string(),
-                                ad.page_id.to_string(),
-                                ad.page_name.to_string(),
-                            ])?;
-
-                            if full {
-                                library_client.app(ad.id).await?;
-                            }
+                            writer.write_record(search_record(ad, reach_columns))?;
+                            ids.push(ad.id);
                         }
                     }
                     Err(error) => {
@@ -110,30 +137,49 @@ async fn main() -> Result<(), Error> {
             }
 
             writer.flush()?;
+
+            if full {
+                let ids = fetch.skip_existing(full_output.as_deref(), ids)?;
+
+                fetch_ads(&library_client, ids, fetch.options(0)).await?;
+            }
         }
         Command::SearchAll {
             creds,
             version,
             query_file,
             country,
+            language,
             output,
             limit,
             full,
             full_output,
+            cookies,
+            profile,
+            proxies,
+            user_agent,
             delay,
+            reach_columns,
+            fetch,
         } => {
             let creds: Creds = toml::from_str(&std::fs::read_to_string(creds)?)?;
             log_token_status(creds.status(Utc::now()));
 
             let client = meta_ads_access::client::Client::new(creds.token, output.as_deref());
-            let library_client =
-                meta_ads_access::library::Client::new::<_, String>(full_output.as_deref(), None)?;
+            let library_client = fetch.apply(library_client(
+                full_output.clone(),
+                cookies,
+                &profile,
+                proxies,
+                user_agent,
+            )?);
 
             let queries = std::fs::read_to_string(&query_file)?;
 
             let mut writer = csv::WriterBuilder::new()
                 .has_headers(false)
                 .from_writer(std::io::stdout());
+            let mut ids = vec![];
 
             for line in queries
                 .lines()
@@ -152,6 +198,7 @@ async fn main() -> Result<(), Error> {
                         terms,
                         countries: &country,
                         search_type,
+                        languages: &language,
                         after: None,
                         limit,
                         delay: std::time::Duration::from_secs(delay),
@@ -162,15 +209,8 @@ async fn main() -> Result<(), Error> {
                     match result.result() {
                         Ok(ads) => {
                             for ad in ads {
-                                writer.write_record([
-                                    ad.id.to_string(),
-                                    ad.page_id.to_string(),
-                                    ad.page_name.to_string(),
-                                ])?;
-
-                                if full {
-                                    library_client.app(ad.id).await?;
-                                }
+                                writer.write_record(search_record(ad, reach_columns))?;
+                                ids.push(ad.id);
                             }
                         }
                         Err(error) => {
@@ -181,13 +221,194 @@ async fn main() -> Result<(), Error> {
             }
 
             writer.flush()?;
+
+            if full {
+                let ids = fetch.skip_existing(full_output.as_deref(), ids)?;
+
+                fetch_ads(&library_client, ids, fetch.options(0)).await?;
+            }
+        }
+        Command::LibraryAd {
+            id,
+            output,
+            cookies,
+            profile,
+            proxies,
+            user_agent,
+        } => {
+            let client = library_client(output, cookies, &profile, proxies, user_agent)?;
+
+            let page = client.app(id).await?;
+
+            if let Some(ad) = page.ad() {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(std::io::stdout());
+
+                writer.write_record(archive_record(&ad, None))?;
+
+                writer.flush()?;
+            } else {
+                ::log::warn!("Missing ad for ad ID: {id}");
+            }
         }
-        Command::LibraryAd { id, output } => {
-            let client = meta_ads_access::library::Client::new::<_, String>(output, None)?;
+        Command::LibrarySearch {
+            query,
+            country,
+            ad_type,
+            active_status,
+            media_type,
+            search_type,
+            output,
+            limit,
+            delay,
+            doc_id,
+            user_agent,
+        } => {
+            let client = library_client(
+                output,
+                None,
+                meta_ads_access::library::cookies::DEFAULT_PROFILE,
+                None,
+                user_agent,
+            )?;
+
+            let params = SearchParams {
+                query,
+                country,
+                ad_type,
+                active_status,
+                media_type,
+                search_type,
+            };
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            let pages = client
+                .search(&meta_ads_access::library::SearchOptions {
+                    params: &params,
+                    doc_id: doc_id.as_deref(),
+                    limit,
+                    delay: std::time::Duration::from_secs(delay),
+                })
+                .await?;
+
+            for ad in pages.into_iter().flat_map(|page| page.ads) {
+                writer.write_record([
+                    ad.ad_archive_id.to_string(),
+                    ad.page_id.to_string(),
+                    ad.page_name.to_string(),
+                ])?;
+            }
+
+            writer.flush()?;
+        }
+        Command::LibraryPage {
+            page_id,
+            country,
+            ad_type,
+            active_status,
+            media_type,
+            output,
+            limit,
+            delay,
+            doc_id,
+            user_agent,
+        } => {
+            let client = library_client(
+                output,
+                None,
+                meta_ads_access::library::cookies::DEFAULT_PROFILE,
+                None,
+                user_agent,
+            )?;
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            for (i, page_id) in page_id.into_iter().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+                }
+
+                ::log::info!("Page: {page_id}");
+
+                let params = PageParams {
+                    page_id,
+                    country,
+                    ad_type,
+                    active_status,
+                    media_type,
+                };
+
+                let pages = client
+                    .page(&meta_ads_access::library::PageOptions {
+                        params: &params,
+                        doc_id: doc_id.as_deref(),
+                        limit,
+                        delay: std::time::Duration::from_secs(delay),
+                    })
+                    .await?;
+
+                for ad in pages.into_iter().flat_map(|page| page.ads) {
+                    writer.write_record([
+                        ad.ad_archive_id.to_string(),
+                        ad.page_id.to_string(),
+                        ad.page_name.to_string(),
+                    ])?;
+                }
+
+                writer.flush()?;
+            }
+        }
+        Command::LibrarySearchArchive {
+            data,
+            most_recent_first,
+        } => {
+            let store = scraper_trail::archive::store::Store::new(data);
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            for (path, contents) in store.contents(most_recent_first)? {
+                let contents = contents?;
+
+                let archive = serde_json::from_str::<
+                    Entry<meta_ads_access::model::library::v2::AdLibrarySearchResponse>,
+                >(&contents)
+                .map_err(|error| Error::JsonFile(path, error))?;
 
-            client.app(id).await?;
+                if let Some(result) = archive.exchange.response.data.result {
+                    for ad in result.into_search_results().ads {
+                        writer.write_record([
+                            ad.ad_archive_id.to_string(),
+                            ad.page_id.to_string(),
+                            ad.page_name.to_string(),
+                        ])?;
+                    }
+                } else {
+                    ::log::warn!(
+                        "Missing ad_library_main for search: {}",
+                        archive.request_params.initial_url()
+                    );
+                }
+            }
+
+            writer.flush()?;
         }
-        Command::LibraryAds { output, delay } => {
+        Command::LibraryAds {
+            output,
+            delay,
+            cookies,
+            profile,
+            proxies,
+            user_agent,
+            fetch,
+        } => {
             let ids = std::io::stdin()
                 .lines()
                 .map(|line| {
@@ -197,12 +418,13 @@ async fn main() -> Result<(), Error> {
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
-            let client = meta_ads_access::library::Client::new::<_, String>(output, None)?;
+            let ids = fetch.skip_existing(output.as_deref(), ids)?;
 
-            for id in ids {
-                client.app(id).await?;
-                tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
-            }
+            let client = fetch.apply(library_client(
+                output, cookies, &profile, proxies, user_agent,
+            )?);
+
+            fetch_ads(&client, ids, fetch.options(delay)).await?;
         }
         Command::UpgradeToken {
             version,
@@ -227,6 +449,7 @@ async fn main() -> Result<(), Error> {
         Command::SearchArchive {
             data,
             most_recent_first,
+            reach_columns,
         } => {
             let store = scraper_trail::archive::store::Store::new(data);
 
@@ -243,11 +466,7 @@ async fn main() -> Result<(), Error> {
                 match archive.exchange.response.data.result() {
                     Ok(ads) => {
                         for ad in ads {
-                            writer.write_record([
-                                ad.id.to_string(),
-                                ad.page_id.to_string(),
-                                ad.page_name.to_string(),
-                            ])?;
+                            writer.write_record(search_record(ad, reach_columns))?;
                         }
                     }
                     Err(error) => {
@@ -258,7 +477,7 @@ async fn main() -> Result<(), Error> {
 
             writer.flush()?;
         }
-        Command::LibraryArchive {
+        Command::SearchBreakdowns {
             data,
             most_recent_first,
         } => {
@@ -271,42 +490,226 @@ async fn main() -> Result<(), Error> {
             for (path, contents) in store.contents(most_recent_first)? {
                 let contents = contents?;
 
-                let archive = serde_json::from_str::<
-                    Entry<meta_ads_access::model::library::v2::AdLibraryResponse>,
-                >(&contents)
-                .map_err(|error| Error::JsonFile(path, error))?;
-
-                let response = archive.exchange.response.data;
+                let archive = serde_json::from_str::<Entry<Response<Ad>>>(&contents)
+                    .map_err(|error| Error::JsonFile(path, error))?;
 
-                if let Some(result) = response.result {
-                    if let Some(ad) = result.ad() {
-                        writer.write_record([
-                            ad.ad_archive_id.to_string(),
-                            ad.snapshot.page_id.to_string(),
-                            ad.snapshot
-                                .link_url
-                                .as_ref()
-                                .map(|link_url| link_url.to_string())
-                                .unwrap_or_default(),
-                        ])?;
+                match archive.exchange.response.data.result() {
+                    Ok(ads) => {
+                        for ad in ads {
+                            for cell in ad.reach_breakdown().cells {
+                                writer.write_record([
+                                    ad.id.to_string(),
+                                    cell.country.to_string(),
+                                    cell.age_range.as_str().to_string(),
+                                    cell.gender.to_string(),
+                                    cell.reach.to_string(),
+                                ])?;
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        ::log::warn!("{}", error.message);
                     }
+                }
+            }
 
-                    for ad in result.search_results().ads {
-                        writer.write_record([
-                            ad.ad_archive_id.to_string(),
-                            ad.snapshot.page_id.to_string(),
-                            ad.snapshot
-                                .link_url
-                                .as_ref()
-                                .map(|link_url| link_url.to_string())
-                                .unwrap_or_default(),
-                        ])?;
+            writer.flush()?;
+        }
+        Command::LibraryArchive {
+            data,
+            most_recent_first,
+            group_collations,
+            skip_invalid,
+        } => {
+            let store = scraper_trail::archive::store::Store::new(data);
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            let mut collator = Collator::default();
+
+            for (path, contents) in store.contents(most_recent_first)? {
+                let contents = contents?;
+
+                let Some(archive) = library_entry(path, &contents, skip_invalid)? else {
+                    continue;
+                };
+
+                match archive.exchange.response.data {
+                    LibraryResponse::V2(result) if group_collations => {
+                        collator.extend(result.ad().cloned().map(IntoBoundedStatic::into_static));
+                        collator.extend(result.into_search_results().ads.into_static());
+                    }
+                    LibraryResponse::V1(ad) if group_collations => {
+                        collator.push_v1(ad.into_static());
                     }
+                    LibraryResponse::Empty => {
+                        ::log::warn!(
+                            "No ad found in either page format for ad ID: {}",
+                            archive.request_params.ad_id
+                        );
+                    }
+                    response => {
+                        for ad in response.ads() {
+                            writer.write_record(archive_record(&ad, [ad.format.to_string()]))?;
+                        }
+                    }
+                }
+            }
+
+            for collated in collator.finish() {
+                let columns = match &collated {
+                    Collated::Group(group) => [
+                        group
+                            .collation_id
+                            .as_ref()
+                            .map(|collation_id| collation_id.to_string())
+                            .unwrap_or_default(),
+                        group.len().to_string(),
+                        group
+                            .members
+                            .iter()
+                            .map(|member| member.ad_archive_id.to_string())
+                            .collect::<Vec<_>>()
+                            .join(";"),
+                        group.differences().len().to_string(),
+                    ],
+                    // Older ads have no collation information, so each is its own group.
+                    Collated::Legacy(_) => [
+                        String::new(),
+                        "1".to_string(),
+                        String::new(),
+                        "0".to_string(),
+                    ],
+                };
+
+                writer.write_record(archive_record(&collated.representative(), columns))?;
+            }
+
+            writer.flush()?;
+        }
+        Command::LibraryProfiles { data, failures } => {
+            let mut counts = std::collections::BTreeMap::<String, ProfileCounts>::new();
+
+            for (path, contents) in
+                scraper_trail::archive::store::Store::new(data).contents(false)?
+            {
+                let contents = contents?;
+
+                let archive = serde_json::from_str::<Entry<LibraryResponse>>(&contents)
+                    .map_err(|error| Error::JsonFile(path, error))?;
+
+                let profile_counts = counts
+                    .entry(
+                        HeaderProfile::identify(&archive.exchange.request)
+                            .map_or_else(|| "none".to_string(), |profile| profile.name.to_string()),
+                    )
+                    .or_default();
+
+                profile_counts.archived += 1;
+
+                if archive.exchange.response.data.ad().is_some() {
+                    profile_counts.extracted += 1;
+                }
+            }
+
+            // The failure directory only exists once a request has failed.
+            let failure_contents =
+                match scraper_trail::archive::store::Store::new(failures).contents(false) {
+                    Ok(contents) => Some(contents),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(error) => return Err(error.into()),
+                };
+
+            for (path, contents) in failure_contents.into_iter().flatten() {
+                let contents = contents?;
+
+                let exchange = serde_json::from_str::<Exchange<'_, Failure>>(&contents)
+                    .map_err(|error| Error::JsonFile(path, error))?;
+
+                let profile_counts = counts
+                    .entry(
+                        exchange
+                            .response
+                            .data
+                            .header_profile
+                            .unwrap_or_else(|| "none".to_string()),
+                    )
+                    .or_default();
+
+                if exchange.response.data.status == 403 || exchange.response.data.block.is_some() {
+                    profile_counts.blocked += 1;
                 } else {
-                    ::log::warn!(
-                        "Missing ad_library_main for ad ID: {}",
-                        archive.request_params.ad_id
-                    );
+                    profile_counts.failed += 1;
+                }
+            }
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            for (profile, profile_counts) in counts {
+                writer.write_record([
+                    profile,
+                    profile_counts.archived.to_string(),
+                    profile_counts.extracted.to_string(),
+                    profile_counts.blocked.to_string(),
+                    profile_counts.failed.to_string(),
+                ])?;
+            }
+
+            writer.flush()?;
+        }
+        Command::LibraryMedia {
+            data,
+            most_recent_first,
+            skip_invalid,
+        } => {
+            use meta_ads_access::model::library::media::MediaAssets;
+
+            let store = scraper_trail::archive::store::Store::new(data);
+
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(std::io::stdout());
+
+            for (path, contents) in store.contents(most_recent_first)? {
+                let contents = contents?;
+
+                let Some(archive) = library_entry(path, &contents, skip_invalid)? else {
+                    continue;
+                };
+
+                match archive.exchange.response.data {
+                    LibraryResponse::V2(result) => {
+                        let ads = result
+                            .ad()
+                            .cloned()
+                            .into_iter()
+                            .chain(result.search_results().ads)
+                            .collect::<Vec<_>>();
+
+                        for ad in ads {
+                            for asset in ad.media_assets() {
+                                writer.write_record(media_record(ad.ad_archive_id, &asset))?;
+                            }
+                        }
+                    }
+                    LibraryResponse::V1(ad) => {
+                        for asset in ad.media_assets() {
+                            writer.write_record(media_record(
+                                ad.deeplink_ad_card.ad_archive_id,
+                                &asset,
+                            ))?;
+                        }
+                    }
+                    LibraryResponse::Empty => {
+                        ::log::warn!(
+                            "No ad found in either page format for ad ID: {}",
+                            archive.request_params.ad_id
+                        );
+                    }
                 }
             }
 
@@ -338,6 +741,9 @@ enum Command {
         terms: String,
         #[clap(long, default_value = "DE")]
         country: Vec<String>,
+        /// ISO 639-1 language code to restrict results to (may be repeated)
+        #[clap(long)]
+        language: Vec<LanguageCode>,
         #[clap(long)]
         exact: bool,
         /// Optional pagination token
@@ -355,9 +761,26 @@ enum Command {
         /// Archive directory to log full requests and responses to
         #[clap(long, default_value = "data/library")]
         full_output: Option<PathBuf>,
+        /// Directory of persistent cookie jars for full downloads (cookies are kept in memory if
+        /// omitted)
+        #[clap(long)]
+        cookies: Option<PathBuf>,
+        /// Cookie jar profile name
+        #[clap(long, default_value = meta_ads_access::library::cookies::DEFAULT_PROFILE)]
+        profile: String,
+        /// TOML file listing proxies to rotate through for full downloads
+        #[clap(long)]
+        proxies: Option<PathBuf>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
         /// Optional duration (in seconds) between requests
         #[clap(long, default_value = "0")]
         delay: u64,
+        /// Add reach by location columns to the output
+        #[clap(long)]
+        reach_columns: bool,
+        #[clap(flatten)]
+        fetch: FetchArgs,
     },
     /// Perform searches for a list of queries provided as lines in the indicated text file
     SearchAll {
@@ -370,6 +793,9 @@ enum Command {
         query_file: PathBuf,
         #[clap(long, default_value = "DE")]
         country: Vec<String>,
+        /// ISO 639-1 language code to restrict results to (may be repeated)
+        #[clap(long)]
+        language: Vec<LanguageCode>,
         /// Archive directory to log requests and responses to
         #[clap(long, default_value = "data/search")]
         output: Option<PathBuf>,
@@ -382,26 +808,141 @@ enum Command {
         /// Archive directory to log full requests and responses to
         #[clap(long, default_value = "data/library")]
         full_output: Option<PathBuf>,
+        /// Directory of persistent cookie jars for full downloads (cookies are kept in memory if
+        /// omitted)
+        #[clap(long)]
+        cookies: Option<PathBuf>,
+        /// Cookie jar profile name
+        #[clap(long, default_value = meta_ads_access::library::cookies::DEFAULT_PROFILE)]
+        profile: String,
+        /// TOML file listing proxies to rotate through for full downloads
+        #[clap(long)]
+        proxies: Option<PathBuf>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
         /// Optional duration (in seconds) between requests
         #[clap(long, default_value = "0")]
         delay: u64,
+        /// Add reach by location columns to the output
+        #[clap(long)]
+        reach_columns: bool,
+        #[clap(flatten)]
+        fetch: FetchArgs,
     },
-    /// Download ad for the specified ID
+    /// Download ad for the specified ID and print its ad ID, page ID, and link URL as CSV
     LibraryAd {
         #[clap(long)]
         id: u64,
         /// Directory to log requests and responses to
         #[clap(long, default_value = "data/library")]
         output: Option<PathBuf>,
+        /// Directory of persistent cookie jars (cookies are kept in memory if omitted)
+        #[clap(long)]
+        cookies: Option<PathBuf>,
+        /// Cookie jar profile name
+        #[clap(long, default_value = meta_ads_access::library::cookies::DEFAULT_PROFILE)]
+        profile: String,
+        /// TOML file listing proxies to rotate through
+        #[clap(long)]
+        proxies: Option<PathBuf>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
+    },
+    /// Search the Ads Library web interface and print the ad IDs, page IDs, and page names as CSV
+    LibrarySearch {
+        #[clap(long)]
+        query: String,
+        /// ISO 3166-1 alpha-2 country code (all countries if omitted)
+        #[clap(long)]
+        country: Option<CountryCode>,
+        /// One of all, political_and_issue_ads, housing_ads, employment_ads, credit_ads
+        #[clap(long, default_value = "all")]
+        ad_type: AdType,
+        /// One of active, inactive, all
+        #[clap(long, default_value = "all")]
+        active_status: ActiveStatus,
+        /// One of all, image, meme, video, none
+        #[clap(long, default_value = "all")]
+        media_type: MediaType,
+        /// One of keyword_unordered, keyword_exact_phrase, page
+        #[clap(long, default_value = "keyword_unordered")]
+        search_type: LibrarySearchType,
+        /// Directory to log requests and responses to
+        #[clap(long, default_value = "data/library-search")]
+        output: Option<PathBuf>,
+        /// Limit to a specified number of pages of results (all if omitted)
+        #[clap(long)]
+        limit: Option<usize>,
+        /// Optional duration (in seconds) between requests
+        #[clap(long, default_value = "0")]
+        delay: u64,
+        /// ID of the web UI's GraphQL pagination query (required for more than one page)
+        #[clap(long)]
+        doc_id: Option<String>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
+    },
+    /// List every ad run by the specified pages on the Ads Library web interface as CSV
+    LibraryPage {
+        /// Page ID (may be repeated)
+        #[clap(long, required = true)]
+        page_id: Vec<u64>,
+        /// ISO 3166-1 alpha-2 country code (all countries if omitted)
+        #[clap(long)]
+        country: Option<CountryCode>,
+        /// One of all, political_and_issue_ads, housing_ads, employment_ads, credit_ads
+        #[clap(long, default_value = "all")]
+        ad_type: AdType,
+        /// One of active, inactive, all
+        #[clap(long, default_value = "all")]
+        active_status: ActiveStatus,
+        /// One of all, image, meme, video, none
+        #[clap(long, default_value = "all")]
+        media_type: MediaType,
+        /// Directory to log requests and responses to
+        #[clap(long, default_value = "data/library-page")]
+        output: Option<PathBuf>,
+        /// Limit to a specified number of pages of results per page ID (all if omitted)
+        #[clap(long)]
+        limit: Option<usize>,
+        /// Optional duration (in seconds) between requests
+        #[clap(long, default_value = "0")]
+        delay: u64,
+        /// ID of the web UI's GraphQL pagination query (required for more than one page)
+        #[clap(long)]
+        doc_id: Option<String>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
+    },
+    /// Print ad IDs, page IDs, and page names as CSV for all archived library searches
+    LibrarySearchArchive {
+        /// Archive directory
+        #[clap(long, default_value = "data/library-search")]
+        data: PathBuf,
+        #[clap(long)]
+        most_recent_first: bool,
     },
     /// Download ads for a list of IDs from standard input
     LibraryAds {
         /// Directory to log requests and responses to
         #[clap(long, default_value = "data/library")]
         output: Option<PathBuf>,
-        /// Optional duration (in seconds) between requests
+        /// Optional duration (in seconds) between requests for each worker
         #[clap(long, default_value = "0")]
         delay: u64,
+        /// Directory of persistent cookie jars (cookies are kept in memory if omitted)
+        #[clap(long)]
+        cookies: Option<PathBuf>,
+        /// Cookie jar profile name
+        #[clap(long, default_value = meta_ads_access::library::cookies::DEFAULT_PROFILE)]
+        profile: String,
+        /// TOML file listing proxies to rotate through
+        #[clap(long)]
+        proxies: Option<PathBuf>,
+        #[clap(flatten)]
+        user_agent: UserAgentArgs,
+        #[clap(flatten)]
+        fetch: FetchArgs,
     },
     /// Upgrade a short-lived token to a long-lived one and save as TOML
     UpgradeToken {
@@ -425,6 +966,17 @@ enum Command {
         data: PathBuf,
         #[clap(long)]
         most_recent_first: bool,
+        /// Add reach by location columns to the output
+        #[clap(long)]
+        reach_columns: bool,
+    },
+    /// Print reach breakdowns as CSV for all archived exchanges (one row per ad, country, age range, and gender)
+    SearchBreakdowns {
+        /// Archive directory
+        #[clap(long, default_value = "data/search")]
+        data: PathBuf,
+        #[clap(long)]
+        most_recent_first: bool,
     },
     LibraryArchive {
         /// Archive directory
@@ -432,7 +984,299 @@ enum Command {
         data: PathBuf,
         #[clap(long)]
         most_recent_first: bool,
+        /// Output one row per collation group instead of one row per ad
+        #[clap(long)]
+        group_collations: bool,
+        /// Log and skip pages that can't be parsed (e.g. with only parts of an older format ad)
+        #[clap(long)]
+        skip_invalid: bool,
     },
+    /// Print the number of archived pages, extracted ads, blocked requests, and other failures for
+    /// each header profile as CSV
+    LibraryProfiles {
+        /// Archive directory
+        #[clap(long, default_value = "data/library")]
+        data: PathBuf,
+        /// Directory of blocked requests and unexpected statuses
+        #[clap(long, default_value = "data/library-failed")]
+        failures: PathBuf,
+    },
+    /// Write an inventory of media assets for ads in a library archive directory as CSV
+    LibraryMedia {
+        /// Archive directory
+        #[clap(long, default_value = "data/library")]
+        data: PathBuf,
+        #[clap(long)]
+        most_recent_first: bool,
+        /// Log and skip pages that can't be parsed (e.g. with only parts of an older format ad)
+        #[clap(long)]
+        skip_invalid: bool,
+    },
+}
+
+/// User agent options for the library commands.
+#[derive(Debug, clap::Args)]
+struct UserAgentArgs {
+    /// User agent to send (defaults to curl's)
+    #[clap(long, conflicts_with = "header_profile")]
+    user_agent: Option<String>,
+    /// Browser header profile (may be repeated to rotate between profiles): curl, chrome-windows,
+    /// chrome-macos, firefox-linux, safari-macos
+    #[clap(long)]
+    header_profile: Vec<HeaderProfile>,
+    /// Directory to archive blocked requests and unexpected statuses to (with the header profile)
+    #[clap(long, default_value = "data/library-failed")]
+    failure_output: Option<PathBuf>,
+}
+
+/// Concurrency and rate limit options for downloading batches of library ads.
+#[derive(Debug, clap::Args)]
+struct FetchArgs {
+    /// Number of ads to download at once
+    #[clap(long, default_value = "1")]
+    workers: usize,
+    /// Maximum average number of library requests per second to each host (unlimited if omitted)
+    #[clap(long, value_parser = parse_rate)]
+    rate_limit: Option<f64>,
+    /// Number of library requests that can be sent at once under the rate limit
+    #[clap(long, default_value = "1", requires = "rate_limit")]
+    burst: u32,
+    /// Skip ads already in the archive directory: "any" (any exchange), "success" (the ad was
+    /// extracted), or a number of days like "30d" (refresh ads downloaded longer ago)
+    #[clap(long)]
+    skip_existing: Option<SkipPolicy>,
+}
+
+impl FetchArgs {
+    fn apply(&self, client: meta_ads_access::library::Client) -> meta_ads_access::library::Client {
+        match self.rate_limit {
+            Some(per_second) => client.with_rate_limit(RateLimit {
+                per_second,
+                burst: self.burst,
+            }),
+            None => client,
+        }
+    }
+
+    /// Remove the IDs that should be skipped because they're already in the archive directory.
+    fn skip_existing(&self, output: Option<&Path>, ids: Vec<u64>) -> Result<Vec<u64>, Error> {
+        let Some(policy) = self.skip_existing else {
+            return Ok(ids);
+        };
+
+        let Some(output) = output else {
+            ::log::warn!("No archive directory to check for existing ads");
+            return Ok(ids);
+        };
+
+        let index = ArchiveIndex::load(output, policy)?;
+        let now = Utc::now();
+        let count = ids.len();
+
+        let ids = ids
+            .into_iter()
+            .filter(|id| !index.skip(*id, policy, now))
+            .collect::<Vec<_>>();
+
+        ::log::info!(
+            "Skipping {} of {count} ads already in {} ({policy})",
+            count - ids.len(),
+            output.display()
+        );
+
+        Ok(ids)
+    }
+
+    const fn options(&self, delay: u64) -> FetchOptions {
+        FetchOptions {
+            workers: self.workers,
+            delay: std::time::Duration::from_secs(delay),
+        }
+    }
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!(
+            "expected a positive number of requests per second: {value}"
+        )),
+    }
+}
+
+/// Download the library pages for a batch of ads, logging failures and continuing with the rest.
+async fn fetch_ads(
+    client: &meta_ads_access::library::Client,
+    ids: Vec<u64>,
+    opts: FetchOptions,
+) -> Result<(), Error> {
+    let count = ids.len();
+    let mut results = client.apps(ids, opts);
+    let mut fetched = 0;
+    let mut failed = 0;
+
+    while let Some((id, result)) = results.recv().await {
+        match result {
+            Ok(page) => {
+                fetched += 1;
+
+                if page.ad().is_none() {
+                    ::log::warn!("No ad found for {id}");
+                }
+
+                ::log::info!("Downloaded ad {id} ({fetched}/{count})");
+            }
+            Err(error) => {
+                failed += 1;
+
+                ::log::error!("Unable to download ad {id}: {error}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        Err(Error::FailedDownloads { failed, count })
+    } else {
+        Ok(())
+    }
+}
+
+/// A library client, using the profile's persistent cookie jar if a cookie directory is given, and
+/// rotating through proxies if a proxy configuration is given.
+fn library_client(
+    output: Option<PathBuf>,
+    cookies: Option<PathBuf>,
+    profile: &str,
+    proxies: Option<PathBuf>,
+    user_agent: UserAgentArgs,
+) -> Result<meta_ads_access::library::Client, Error> {
+    let client = meta_ads_access::library::Client::new(output, user_agent.user_agent)?
+        .with_header_profiles(user_agent.header_profile);
+
+    let client = match user_agent.failure_output {
+        Some(failure_output) => client.with_failure_output(failure_output),
+        None => client,
+    };
+
+    let client = match &cookies {
+        Some(cookies) => {
+            let cookie_jar =
+                meta_ads_access::library::cookies::CookieJar::load_profile(cookies, profile)
+                    .map_err(meta_ads_access::library::Error::from)?;
+
+            ::log::info!(
+                "Loaded {} cookies from {}",
+                cookie_jar.len(),
+                cookie_jar.path().display()
+            );
+
+            client.with_cookie_jar(cookie_jar)?
+        }
+        None => client,
+    };
+
+    Ok(match proxies {
+        Some(proxies) => {
+            let config = meta_ads_access::library::proxy::PoolConfig::load(proxies)
+                .map_err(meta_ads_access::library::Error::from)?;
+
+            ::log::info!("Using {} proxies", config.proxies.len());
+
+            client.with_proxy_pool(
+                &config,
+                cookies.as_deref().map(|cookies| (cookies, profile)),
+            )?
+        }
+        None => client,
+    })
+}
+
+/// Library requests for a header profile, archived or failed.
+#[derive(Clone, Copy, Debug, Default)]
+struct ProfileCounts {
+    archived: usize,
+    extracted: usize,
+    blocked: usize,
+    failed: usize,
+}
+
+/// Parse a library archive entry, logging and skipping it if it's invalid and `skip_invalid` is set.
+fn library_entry(
+    path: PathBuf,
+    contents: &str,
+    skip_invalid: bool,
+) -> Result<Option<Entry<'_, LibraryResponse<'_>>>, Error> {
+    match serde_json::from_str::<Entry<LibraryResponse>>(contents) {
+        Ok(archive) => Ok(Some(archive)),
+        Err(error) if skip_invalid => {
+            ::log::warn!("Skipping {}: {error}", path.display());
+
+            Ok(None)
+        }
+        Err(error) => Err(Error::JsonFile(path, error)),
+    }
+}
+
+/// The ad ID, role, kind, resolution, watermark flag, and URL of a media asset.
+fn media_record(
+    ad_id: u64,
+    asset: &meta_ads_access::model::library::media::MediaAsset<'_>,
+) -> [String; 6] {
+    [
+        ad_id.to_string(),
+        asset.role.to_string(),
+        asset.kind.to_string(),
+        asset.resolution.to_string(),
+        asset.watermarked.to_string(),
+        asset.url.to_string(),
+    ]
+}
+
+/// The ad ID, page ID, and link URL of a library ad, followed by the given columns.
+fn archive_record<I: IntoIterator<Item = String>>(ad: &AdSummary<'_>, columns: I) -> Vec<String> {
+    [
+        ad.ad_id.to_string(),
+        ad.page_id.to_string(),
+        ad.link_url.unwrap_or_default().to_string(),
+    ]
+    .into_iter()
+    .chain(columns)
+    .collect()
+}
+
+/// The ad ID, page ID, and page name, optionally followed by the total reach by location, the
+/// EU total reach, the top country, its share of the total, and whether the totals are consistent
+/// (empty without location data).
+fn search_record(ad: &Ad<'_>, reach_columns: bool) -> Vec<String> {
+    let mut record = vec![
+        ad.id.to_string(),
+        ad.page_id.to_string(),
+        ad.page_name.to_string(),
+    ];
+
+    if reach_columns {
+        let reach = ad.reach_by_location();
+        let top = reach.top(1).into_iter().next();
+
+        record.extend([
+            reach.total().to_string(),
+            ad.eu_total_reach
+                .map(|eu_total_reach| eu_total_reach.to_string())
+                .unwrap_or_default(),
+            top.map(|(country, _)| country.to_string())
+                .unwrap_or_default(),
+            top.and_then(|(country, _)| country.code())
+                .and_then(|code| reach.share(code))
+                .map(|share| format!("{share:.4}"))
+                .unwrap_or_default(),
+            ad.reach_consistency()
+                .is_consistent()
+                .map(|consistent| consistent.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+
+    record
 }
 
 fn log_token_status(status: meta_ads_access::token::TokenStatus) {
@@ -23,6 +23,8 @@ pub struct SearchOptions<'a> {
     pub terms: &'a str,
     pub countries: &'a [String],
     pub search_type: request::SearchType,
+    /// Languages to restrict results to (all languages if empty).
+    pub languages: &'a [crate::model::language::LanguageCode],
     /// Optional pagination cursor to resume from.
     pub after: Option<&'a str>,
     /// Maximum number of pages to fetch.
@@ -62,7 +64,8 @@ impl Client {
             opts.countries,
             opts.search_type,
             opts.after,
-        );
+        )
+        .with_languages(opts.languages);
 
         let request = params.build_request(None);
         let exchange = scraper_trail::client::json_send(&self.underlying, request).await?;
@@ -1,3 +1,4 @@
+use crate::model::language::LanguageCode;
 use crate::version::GraphApiVersion;
 use chrono::{DateTime, Utc};
 use regex::Regex;
@@ -90,6 +91,7 @@ pub struct Params<'a> {
     pub terms: Cow<'a, str>,
     pub countries: Vec<Cow<'a, str>>,
     pub search_type: SearchType,
+    pub languages: Vec<LanguageCode>,
     pub after: Option<Cow<'a, str>>,
 }
 
@@ -111,10 +113,18 @@ impl<'a> Params<'a> {
             terms: terms.into(),
             countries: countries.iter().map(std::convert::Into::into).collect(),
             search_type,
+            languages: vec![],
             after: after.map(std::convert::Into::into),
         }
     }
 
+    /// Restrict results to ads in the given languages.
+    #[must_use]
+    pub fn with_languages(mut self, languages: &[LanguageCode]) -> Self {
+        self.languages = languages.to_vec();
+        self
+    }
+
     const fn error() -> ParseError {
         ParseError::InvalidUrl {
             expected: EXPECTED_URL_MESSAGE,
@@ -167,6 +177,27 @@ impl<'a> Params<'a> {
                 |search_type| search_type.parse().ok(),
             )?;
 
+            let languages = query_params.get("languages").map_or_else(
+                || Some(vec![]),
+                |languages| {
+                    BRACKETED_RE
+                        .captures(languages)
+                        .and_then(|languages| languages.get(1))
+                        .and_then(|languages| {
+                            languages
+                                .as_str()
+                                .split(',')
+                                .map(|language| {
+                                    QUOTED_RE
+                                        .captures(language)
+                                        .and_then(|language| language.get(1))
+                                        .and_then(|language| language.as_str().parse().ok())
+                                })
+                                .collect()
+                        })
+                },
+            )?;
+
             let after = query_params
                 .get("after")
                 .map(|after| after.to_string().into());
@@ -178,6 +209,7 @@ impl<'a> Params<'a> {
                 terms,
                 countries,
                 search_type,
+                languages,
                 after,
             })
         } else {
@@ -199,9 +231,23 @@ impl<'a> scraper_trail::request::params::Params for Params<'a> {
             .collect::<Vec<_>>();
         let ad_reached_countries = format!("[{}]", ad_reached_countries.join(","));
         let fields = FIELDS.join(",");
+        let languages = if self.languages.is_empty() {
+            String::new()
+        } else {
+            let languages = self
+                .languages
+                .iter()
+                .map(|language| format!("'{language}'"))
+                .collect::<Vec<_>>();
+
+            format!(
+                "&languages={}",
+                urlencoding::encode(&format!("[{}]", languages.join(",")))
+            )
+        };
 
         let url = format!(
-            "{BASE_URL}/v{}/ads_archive?search_terms='{}'&ad_reached_countries={}&fields={}&access_token={}&unmask_removed_content={}{}{}",
+            "{BASE_URL}/v{}/ads_archive?search_terms='{}'&ad_reached_countries={}&fields={}&access_token={}&unmask_removed_content={}{}{languages}{}",
             self.version,
             urlencoding::encode(&self.terms),
             urlencoding::encode(&ad_reached_countries),
@@ -0,0 +1,292 @@
+//! Detection of block pages and bounded handling of the library's bot challenges.
+
+use regex::Regex;
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+
+const MAX_REDIRECTS: usize = 10;
+
+static CHALLENGE_URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"fetch\s*\(\s*["\'](/__rd_verify[^"\']+)["\']"#).unwrap());
+static CHECKPOINT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?:action=|location(?:\.href)?\s*=|location\.replace\()\s*["'][^"']*/checkpoint/"#,
+    )
+    .unwrap()
+});
+static LOGIN_FORM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<form[^>]+id="login_form""#).unwrap());
+static RATE_LIMIT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)temporarily blocked|you(?:'|’)re going too fast|rate limit exceeded").unwrap()
+});
+
+/// A page that prevents access to the library and can't be solved automatically.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    LoginWall,
+    RateLimit,
+    Checkpoint,
+}
+
+impl BlockKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::LoginWall => "login_wall",
+            Self::RateLimit => "rate_limit",
+            Self::Checkpoint => "checkpoint",
+        }
+    }
+
+    /// Identify a block page from its status code and body.
+    ///
+    /// Pages containing library data are never considered blocked, since ad copy could match.
+    #[must_use]
+    pub fn detect(status_code: StatusCode, body: &str) -> Option<Self> {
+        if status_code == StatusCode::TOO_MANY_REQUESTS {
+            Some(Self::RateLimit)
+        } else if body.contains("ad_library_main") || body.contains("deeplinkAdCard") {
+            None
+        } else if RATE_LIMIT_RE.is_match(body) {
+            Some(Self::RateLimit)
+        } else if CHECKPOINT_RE.is_match(body) {
+            Some(Self::Checkpoint)
+        } else if LOGIN_FORM_RE.is_match(body) {
+            Some(Self::LoginWall)
+        } else {
+            None
+        }
+    }
+
+    /// Identify a redirect to a block page.
+    #[must_use]
+    pub fn from_redirect(url: &url::Url) -> Option<Self> {
+        let path = url.path();
+
+        if path.starts_with("/checkpoint") {
+            Some(Self::Checkpoint)
+        } else if path.starts_with("/login") {
+            Some(Self::LoginWall)
+        } else {
+            None
+        }
+    }
+
+    /// Find a block redirect in the source chain of an HTTP client error.
+    #[must_use]
+    pub fn from_reqwest_error(error: &reqwest::Error) -> Option<Self> {
+        let mut source = std::error::Error::source(error);
+
+        while let Some(error) = source {
+            if let Some(BlockedRedirect(kind)) = error.downcast_ref() {
+                return Some(*kind);
+            }
+
+            source = error.source();
+        }
+
+        None
+    }
+}
+
+impl Display for BlockKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Redirected to block page ({0})")]
+struct BlockedRedirect(BlockKind);
+
+/// Follow redirects as usual, except for redirects to login and checkpoint pages.
+#[must_use]
+pub fn redirect_policy() -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(|attempt| {
+        if let Some(kind) = BlockKind::from_redirect(attempt.url()) {
+            attempt.error(BlockedRedirect(kind))
+        } else if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// The result of an attempt to solve a challenge.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChallengeOutcome {
+    /// The challenge was found and the verification request succeeded.
+    Solved,
+    /// The response didn't contain a challenge.
+    NoChallengeFound,
+    /// The challenge was found but the verification request was rejected.
+    StillBlocked,
+}
+
+/// The number of challenge attempts and the delays between them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ChallengePolicy {
+    /// The maximum number of challenges to solve for a single request.
+    pub max_attempts: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ChallengePolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ChallengePolicy {
+    /// The delay before retrying after the given (one-based) attempt, doubling each time.
+    #[must_use]
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let exponent = u32::try_from(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX)
+            .min(16);
+
+        self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff)
+    }
+}
+
+/// Per-host gates held while a challenge is handled, so that only one worker solves it.
+///
+/// Shared by all clones of a library client.
+#[derive(Debug, Default)]
+pub struct ChallengeGates {
+    gates: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ChallengeGates {
+    /// Take the host's gate to handle a challenge, or wait until the worker holding it is done
+    /// (returning `None`, since the request should be retried before solving another challenge).
+    pub async fn enter(&self, host: &str) -> Option<tokio::sync::OwnedMutexGuard<()>> {
+        let gate = self
+            .gates
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(host.to_string())
+            .or_default()
+            .clone();
+
+        if let Ok(guard) = gate.clone().try_lock_owned() {
+            Some(guard)
+        } else {
+            drop(gate.lock().await);
+
+            None
+        }
+    }
+}
+
+/// The path of the verification request in a challenge page.
+#[must_use]
+pub fn challenge_path(body: &str) -> Option<&str> {
+    CHALLENGE_URL_RE
+        .captures(body)
+        .and_then(|captures| captures.get(1))
+        .map(|path| path.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_block_pages() {
+        assert_eq!(
+            BlockKind::detect(StatusCode::TOO_MANY_REQUESTS, ""),
+            Some(BlockKind::RateLimit)
+        );
+        assert_eq!(
+            BlockKind::detect(StatusCode::OK, "<h2>You’re Temporarily Blocked</h2>"),
+            Some(BlockKind::RateLimit)
+        );
+        assert_eq!(
+            BlockKind::detect(
+                StatusCode::OK,
+                r#"<script>window.location.replace("https://www.facebook.com/checkpoint/1501092823525282/");</script>"#
+            ),
+            Some(BlockKind::Checkpoint)
+        );
+        assert_eq!(
+            BlockKind::detect(
+                StatusCode::OK,
+                r#"<form id="login_form" action="/login/device-based/regular/login/" method="post">"#
+            ),
+            Some(BlockKind::LoginWall)
+        );
+        assert_eq!(
+            BlockKind::detect(
+                StatusCode::OK,
+                r#"<form id="login_form"></form><script type="application/json">{"ad_library_main":{}}</script>"#
+            ),
+            None
+        );
+        assert_eq!(
+            BlockKind::from_redirect(
+                &"https://www.facebook.com/checkpoint/block/"
+                    .parse()
+                    .unwrap()
+            ),
+            Some(BlockKind::Checkpoint)
+        );
+        assert_eq!(
+            BlockKind::from_redirect(&"https://www.facebook.com/ads/library/".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn bounded_backoff() {
+        let policy = ChallengePolicy::default();
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(10), Duration::from_secs(30));
+        assert_eq!(policy.backoff(usize::MAX), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn one_challenge_per_host() {
+        let gates = Arc::new(ChallengeGates::default());
+        let guard = gates.enter("www.facebook.com").await;
+
+        assert!(guard.is_some());
+        assert!(gates.enter("example.com").await.is_some());
+
+        let waiting = tokio::spawn({
+            let gates = gates.clone();
+
+            async move { gates.enter("www.facebook.com").await.is_some() }
+        });
+
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(guard);
+        assert!(!waiting.await.unwrap());
+        assert!(gates.enter("www.facebook.com").await.is_some());
+    }
+
+    #[test]
+    fn find_challenge_path() {
+        assert_eq!(
+            challenge_path(r"fetch('/__rd_verify_abc?challenge=2', {method: 'POST'})"),
+            Some("/__rd_verify_abc?challenge=2")
+        );
+        assert_eq!(challenge_path("<html></html>"), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! A cookie jar that persists library scraping sessions (and solved challenges) across runs.
+
+use reqwest::header::HeaderValue;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The minimum time between saves after successful responses.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Cookie store error")]
+    Store(#[from] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Invalid profile name")]
+    InvalidProfile(String),
+}
+
+/// A cookie jar backed by a JSON file.
+///
+/// Expired cookies are dropped when the jar is loaded and when it is saved. Session cookies are
+/// saved, since the challenge cookies may not have an explicit expiry. Any changes that haven't
+/// been saved yet are saved when the jar is dropped.
+#[derive(Debug)]
+pub struct CookieJar {
+    store: RwLock<cookie_store::CookieStore>,
+    path: PathBuf,
+    /// The time of the last save, locked while saving so that saves don't interleave.
+    last_saved: Mutex<Option<Instant>>,
+    changed: AtomicBool,
+}
+
+impl CookieJar {
+    /// The jar file for a profile in the given directory.
+    ///
+    /// Profile names can't be empty or contain path separators or `..`, so the file is always
+    /// directly in the directory.
+    pub fn profile_path<P: AsRef<Path>>(dir: P, profile: &str) -> Result<PathBuf, Error> {
+        if profile.is_empty() || profile.contains(['/', '\\']) || profile.contains("..") {
+            Err(Error::InvalidProfile(profile.to_string()))
+        } else {
+            Ok(dir.as_ref().join(format!("{profile}.json")))
+        }
+    }
+
+    /// Load the jar from the given file, or create an empty jar if the file doesn't exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+
+        let store = match std::fs::File::open(&path) {
+            Ok(file) => cookie_store::serde::json::load(BufReader::new(file))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                cookie_store::CookieStore::default()
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            store: RwLock::new(store),
+            path,
+            last_saved: Mutex::new(None),
+            changed: AtomicBool::new(false),
+        })
+    }
+
+    /// Load the jar for a profile in the given directory.
+    pub fn load_profile<P: AsRef<Path>>(dir: P, profile: &str) -> Result<Self, Error> {
+        Self::load(Self::profile_path(dir, profile)?)
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of unexpired cookies.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.store
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter_unexpired()
+            .count()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the unexpired cookies to the jar's file.
+    pub fn save(&self) -> Result<(), Error> {
+        let mut last_saved = self
+            .last_saved
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        self.write()?;
+        *last_saved = Some(Instant::now());
+        drop(last_saved);
+
+        Ok(())
+    }
+
+    /// Save the jar if its cookies have changed and it hasn't been saved recently.
+    pub fn save_if_due(&self) -> Result<(), Error> {
+        let mut last_saved = self
+            .last_saved
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let due = last_saved.is_none_or(|last_saved| last_saved.elapsed() >= SAVE_INTERVAL);
+
+        if due && self.changed.load(Ordering::Acquire) {
+            self.write()?;
+            *last_saved = Some(Instant::now());
+        }
+
+        drop(last_saved);
+
+        Ok(())
+    }
+
+    /// Write to a temporary file that replaces the jar's file, so a failed write can't truncate it.
+    fn write(&self) -> Result<(), Error> {
+        // Changes made from here on will be saved next time.
+        self.changed.store(false, Ordering::Release);
+
+        let unexpired = {
+            let store = self.store.read().unwrap_or_else(PoisonError::into_inner);
+
+            cookie_store::CookieStore::from_cookies(
+                store
+                    .iter_unexpired()
+                    .map(|cookie| Ok::<_, Error>(cookie.clone())),
+                false,
+            )?
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(format!(".{}.tmp", std::process::id()));
+
+        let mut writer = BufWriter::new(std::fs::File::create(&temp_path)?);
+
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(&unexpired, &mut writer)?;
+
+        writer
+            .into_inner()
+            .map_err(std::io::IntoInnerError::into_error)?
+            .sync_all()?;
+        std::fs::rename(temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for CookieJar {
+    fn drop(&mut self) {
+        if self.changed.load(Ordering::Acquire)
+            && let Err(error) = self.write()
+        {
+            ::log::warn!("Unable to save cookie jar {}: {error}", self.path.display());
+        }
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| cookie_store::RawCookie::parse(value.to_string()).ok())
+            })
+            .collect::<Vec<_>>();
+
+        if cookies.is_empty() {
+            return;
+        }
+
+        self.store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .store_response_cookies(cookies.into_iter(), url);
+
+        self.changed.store(true, Ordering::Release);
+    }
+
+    fn cookies(&self, url: &url::Url) -> Option<HeaderValue> {
+        let value = self
+            .store
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if value.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&value).ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    #[test]
+    fn save_and_load_respects_expiry() -> Result<(), Box<dyn std::error::Error>> {
+        let dir =
+            std::env::temp_dir().join(format!("meta-ads-access-cookies-{}", std::process::id()));
+        let url: url::Url = "https://www.facebook.com/ads/library/".parse()?;
+
+        let jar = CookieJar::load_profile(&dir, "test")?;
+        assert!(jar.is_empty());
+
+        let headers = [
+            HeaderValue::from_static("rd_challenge=abc; Path=/; Max-Age=3600"),
+            HeaderValue::from_static("session=def; Path=/"),
+            HeaderValue::from_static("old=ghi; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT"),
+        ];
+
+        jar.set_cookies(&mut headers.iter(), &url);
+        jar.save()?;
+
+        let loaded = CookieJar::load_profile(&dir, "test")?;
+
+        let header = loaded.cookies(&url).ok_or("missing cookies")?;
+        let mut values = header.to_str()?.split("; ").collect::<Vec<_>>();
+        values.sort_unstable();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(values, vec!["rd_challenge=abc", "session=def"]);
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn debounced_concurrent_and_final_saves() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!(
+            "meta-ads-access-cookie-saves-{}",
+            std::process::id()
+        ));
+        let url: url::Url = "https://www.facebook.com/ads/library/".parse()?;
+        let set = |jar: &CookieJar, value: &'static str| {
+            jar.set_cookies(&mut std::iter::once(&HeaderValue::from_static(value)), &url);
+        };
+
+        let jar = CookieJar::load_profile(&dir, "test")?;
+
+        set(&jar, "a=1; Path=/");
+        jar.save_if_due()?;
+        set(&jar, "b=2; Path=/");
+        jar.save_if_due()?;
+
+        assert_eq!(CookieJar::load_profile(&dir, "test")?.len(), 1);
+
+        std::thread::scope(|scope| -> Result<(), Box<dyn std::error::Error>> {
+            let saves = (0..8)
+                .map(|_| scope.spawn(|| jar.save()))
+                .collect::<Vec<_>>();
+
+            for save in saves {
+                save.join().map_err(|_| "save panicked")??;
+            }
+
+            Ok(())
+        })?;
+
+        assert_eq!(CookieJar::load_profile(&dir, "test")?.len(), 2);
+
+        set(&jar, "c=3; Path=/");
+        drop(jar);
+
+        assert_eq!(CookieJar::load_profile(&dir, "test")?.len(), 3);
+        assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_profiles_outside_directory() {
+        for profile in ["", "../x", "a/b", "a\\b", ".."] {
+            assert!(matches!(
+                CookieJar::profile_path("cookies", profile),
+                Err(Error::InvalidProfile(_))
+            ));
+        }
+
+        assert!(CookieJar::profile_path("cookies", "scraper-1.eu").is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+//! Browser-like user agent and header profiles for library requests.
+
+use scraper_trail::request::Request;
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+const CHROME_SEC_CH_UA: &str =
+    r#""Chromium";v="138", "Google Chrome";v="138", "Not)A;Brand";v="24""#;
+const NAVIGATION_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+static BUILTIN: LazyLock<Vec<HeaderProfile>> = LazyLock::new(|| {
+    vec![
+        HeaderProfile::new("curl", "curl/8.16.0", &[("accept", "*/*")]),
+        HeaderProfile::new(
+            "chrome-windows",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36",
+            &[
+                ("accept", NAVIGATION_ACCEPT),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("sec-ch-ua", CHROME_SEC_CH_UA),
+                ("sec-ch-ua-mobile", "?0"),
+                ("sec-ch-ua-platform", r#""Windows""#),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+                ("sec-fetch-user", "?1"),
+                ("upgrade-insecure-requests", "1"),
+            ],
+        ),
+        HeaderProfile::new(
+            "chrome-macos",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36",
+            &[
+                ("accept", NAVIGATION_ACCEPT),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("sec-ch-ua", CHROME_SEC_CH_UA),
+                ("sec-ch-ua-mobile", "?0"),
+                ("sec-ch-ua-platform", r#""macOS""#),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+                ("sec-fetch-user", "?1"),
+                ("upgrade-insecure-requests", "1"),
+            ],
+        ),
+        HeaderProfile::new(
+            "firefox-linux",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:141.0) Gecko/20100101 Firefox/141.0",
+            &[
+                ("accept", NAVIGATION_ACCEPT),
+                ("accept-language", "en-US,en;q=0.5"),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+                ("sec-fetch-user", "?1"),
+                ("upgrade-insecure-requests", "1"),
+            ],
+        ),
+        HeaderProfile::new(
+            "safari-macos",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.5 Safari/605.1.15",
+            &[
+                ("accept", NAVIGATION_ACCEPT),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+            ],
+        ),
+    ]
+});
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Unknown header profile")]
+    UnknownProfile(String),
+}
+
+/// A user agent and the headers a browser would send with it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeaderProfile {
+    pub name: Cow<'static, str>,
+    pub user_agent: Cow<'static, str>,
+    pub headers: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+}
+
+impl HeaderProfile {
+    fn new(
+        name: &'static str,
+        user_agent: &'static str,
+        headers: &[(&'static str, &'static str)],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            user_agent: user_agent.into(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| ((*name).into(), (*value).into()))
+                .collect(),
+        }
+    }
+
+    /// The built-in profiles.
+    #[must_use]
+    pub fn builtin() -> &'static [Self] {
+        &BUILTIN
+    }
+
+    #[must_use]
+    pub fn find(name: &str) -> Option<&'static Self> {
+        BUILTIN.iter().find(|profile| profile.name == name)
+    }
+
+    /// Add the profile's headers (and user agent) to a request, keeping any the request already
+    /// sets (e.g. for GraphQL requests).
+    pub fn apply(&self, request: &mut Request<'_>) {
+        let headers = std::iter::once((Cow::Borrowed("user-agent"), self.user_agent.clone()))
+            .chain(self.headers.iter().cloned());
+
+        for (name, value) in headers {
+            if !request
+                .headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case(&name))
+            {
+                request.headers.insert(name, value);
+            }
+        }
+    }
+
+    /// The built-in profile whose user agent was sent with an archived request (if any).
+    #[must_use]
+    pub fn identify(request: &Request<'_>) -> Option<&'static Self> {
+        let user_agent = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("user-agent"))
+            .map(|(_, value)| value)?;
+
+        BUILTIN
+            .iter()
+            .find(|profile| profile.user_agent == *user_agent)
+    }
+}
+
+impl FromStr for HeaderProfile {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::find(s)
+            .cloned()
+            .ok_or_else(|| Error::UnknownProfile(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_and_identify_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let profile: HeaderProfile = "chrome-windows".parse()?;
+        let mut request = Request::new(
+            "https://www.facebook.com/api/graphql/",
+            None,
+            None,
+            Some(vec![("Accept", "*/*")]),
+            None::<String>,
+        )?;
+
+        assert_eq!(HeaderProfile::identify(&request), None);
+
+        profile.apply(&mut request);
+
+        assert_eq!(request.headers["Accept"], "*/*");
+        assert!(!request.headers.contains_key("accept"));
+        assert_eq!(request.headers["sec-ch-ua-platform"], r#""Windows""#);
+        assert!(request.headers["user-agent"].contains("Chrome/138"));
+        assert_eq!(HeaderProfile::identify(&request), Some(&profile));
+        assert!("netscape".parse::<HeaderProfile>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! An index of the ads already downloaded to a library archive, used to skip them in new runs.
+
+use crate::model::library::versioned::LibraryResponse;
+use chrono::{DateTime, TimeDelta, Utc};
+use scraper_trail::archive::entry::Entry;
+use scraper_trail::request::Request;
+use scraper_trail::request::params::Params as _;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid skip policy")]
+    InvalidSkipPolicy(String),
+}
+
+/// When an ad that is already in the archive should be skipped.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SkipPolicy {
+    /// Skip the ad if there is any exchange for it.
+    Any,
+    /// Skip the ad only if the ad could be extracted from one of its exchanges.
+    Success,
+    /// Skip the ad unless its most recent exchange is older than the given number of days.
+    OlderThan(u32),
+}
+
+impl SkipPolicy {
+    /// Whether the ad with the given index entry should be skipped.
+    #[must_use]
+    pub fn skip(self, entry: &IndexEntry, now: DateTime<Utc>) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Success => entry.extracted,
+            Self::OlderThan(days) => now - entry.latest < TimeDelta::days(days.into()),
+        }
+    }
+}
+
+impl Display for SkipPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("any"),
+            Self::Success => f.write_str("success"),
+            Self::OlderThan(days) => write!(f, "{days}d"),
+        }
+    }
+}
+
+impl FromStr for SkipPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(Self::Any),
+            "success" => Ok(Self::Success),
+            other => other
+                .strip_suffix('d')
+                .and_then(|days| days.parse().ok())
+                .map(Self::OlderThan)
+                .ok_or_else(|| Error::InvalidSkipPolicy(s.to_string())),
+        }
+    }
+}
+
+/// What the archive contains for an ad.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IndexEntry {
+    /// The timestamp of the most recent exchange.
+    pub latest: DateTime<Utc>,
+    /// Whether the ad could be extracted (in either page format) from any of the exchanges (only
+    /// checked for the [`SkipPolicy::Success`] policy).
+    pub extracted: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct RequestOnly<'a> {
+    #[serde(borrow)]
+    request: Request<'a>,
+}
+
+/// The ads in a library archive directory, indexed by ad ID.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveIndex {
+    entries: HashMap<u64, IndexEntry>,
+}
+
+impl ArchiveIndex {
+    /// Index the archive directory (which may not exist yet), only parsing the responses if the
+    /// policy requires it.
+    ///
+    /// Files that aren't ad page exchanges are ignored.
+    pub fn load<P: AsRef<Path>>(base: P, policy: SkipPolicy) -> Result<Self, Error> {
+        let store = scraper_trail::archive::store::Store::new(base);
+        let mut index = Self::default();
+
+        let contents = match store.contents(false) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(index),
+            Err(error) => return Err(error.into()),
+        };
+
+        for (path, contents) in contents {
+            let contents = contents?;
+
+            let Some((ad_id, timestamp)) = serde_json::from_str::<RequestOnly<'_>>(&contents)
+                .ok()
+                .and_then(|entry| {
+                    crate::library::request::Params::parse_request(&entry.request)
+                        .ok()
+                        .map(|params| (params.ad_id, entry.request.timestamp))
+                })
+            else {
+                ::log::debug!("Not indexing {}", path.display());
+                continue;
+            };
+
+            let extracted = policy == SkipPolicy::Success
+                && serde_json::from_str::<Entry<'_, LibraryResponse<'_>>>(&contents)
+                    .is_ok_and(|entry| entry.exchange.response.data.ad().is_some());
+
+            index.insert(ad_id, timestamp, extracted);
+        }
+
+        Ok(index)
+    }
+
+    fn insert(&mut self, ad_id: u64, timestamp: DateTime<Utc>, extracted: bool) {
+        let entry = self.entries.entry(ad_id).or_insert(IndexEntry {
+            latest: timestamp,
+            extracted,
+        });
+
+        entry.latest = entry.latest.max(timestamp);
+        entry.extracted |= extracted;
+    }
+
+    #[must_use]
+    pub fn get(&self, ad_id: u64) -> Option<&IndexEntry> {
+        self.entries.get(&ad_id)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the ad should be skipped under the given policy.
+    #[must_use]
+    pub fn skip(&self, ad_id: u64, policy: SkipPolicy, now: DateTime<Utc>) -> bool {
+        self.get(ad_id).is_some_and(|entry| policy.skip(entry, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AD_01_EXAMPLE: &str = include_str!("../../examples/library/v2-ad-01.json");
+
+    fn write_exchange(
+        base: &Path,
+        ad_id: u64,
+        timestamp: DateTime<Utc>,
+        data: &str,
+    ) -> std::io::Result<()> {
+        std::fs::write(
+            base.join(format!("{}.json", timestamp.timestamp_millis())),
+            format!(
+                r#"{{"request":{{"url":"https://www.facebook.com/ads/library/?id={ad_id}","timestamp_ms":"{}"}},"response":{{"headers":{{}},"data":{data}}}}}"#,
+                timestamp.timestamp_millis()
+            ),
+        )
+    }
+
+    #[test]
+    fn skip_existing_ads() -> Result<(), Box<dyn std::error::Error>> {
+        let base =
+            std::env::temp_dir().join(format!("meta-ads-access-index-{}", std::process::id()));
+        std::fs::create_dir_all(&base)?;
+
+        let now = Utc::now();
+        let old = now - TimeDelta::days(40);
+
+        write_exchange(&base, 576_535_441_426_103, old, AD_01_EXAMPLE)?;
+        write_exchange(&base, 1, old - TimeDelta::days(1), "[]")?;
+        write_exchange(&base, 1, now - TimeDelta::days(2), "[]")?;
+        std::fs::write(base.join("notes.txt"), "not an exchange")?;
+
+        let index = ArchiveIndex::load(&base, SkipPolicy::Success)?;
+        let policy: SkipPolicy = "30d".parse()?;
+
+        assert_eq!(index.len(), 2);
+        assert!(index.skip(576_535_441_426_103, SkipPolicy::Success, now));
+        assert!(!index.skip(576_535_441_426_103, policy, now));
+        assert!(index.skip(1, SkipPolicy::Any, now));
+        assert!(!index.skip(1, SkipPolicy::Success, now));
+        assert!(index.skip(1, policy, now));
+        assert!(!index.skip(2, SkipPolicy::Any, now));
+        assert_eq!(policy.to_string(), "30d");
+        assert!("sometimes".parse::<SkipPolicy>().is_err());
+
+        assert!(ArchiveIndex::load(base.join("missing"), SkipPolicy::Any)?.is_empty());
+
+        std::fs::remove_dir_all(base)?;
+
+        Ok(())
+    }
+}
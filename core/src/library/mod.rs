@@ -1,20 +1,33 @@
-use regex::Regex;
+use crate::model::library::v2::{AdLibraryResult, SearchResults};
+use crate::model::library::versioned::{AdSummary, LibraryResponse};
+use bounded_static::IntoBoundedStatic;
+use challenge::{BlockKind, ChallengeGates, ChallengeOutcome, ChallengePolicy};
 use reqwest::StatusCode;
 use scraper::Selector;
+use scraper_trail::exchange::Exchange;
+use scraper_trail::request::Request;
 use scraper_trail::request::params::Params;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::mpsc;
 
+pub mod challenge;
+pub mod cookies;
+pub mod headers;
+pub mod index;
+pub mod proxy;
+pub mod rate_limit;
 pub mod request;
 
 const DEFAULT_USER_AGENT: &str = "curl/8.16.0";
+const GRAPHQL_RESPONSE_PREFIX: &str = "for (;;);";
 
 static JSON_SCRIPT_SEL: LazyLock<Selector> =
     LazyLock::new(|| Selector::parse(r#"script[type="application/json"]"#).unwrap());
 
-static CHALLENGE_URL_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"fetch\s*\(\s*["\'](/__rd_verify[^"\']+)["\']"#).unwrap());
-
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("I/O error")]
@@ -25,14 +38,108 @@ pub enum Error {
     Json(#[from] serde_json::Error),
     #[error("Scraper client error")]
     ScraperClient(#[from] scraper_trail::client::Error),
-    #[error("Unable to acquire challenge cookies")]
-    ChallengeCookies,
+    #[error("Library model error")]
+    Model(#[from] crate::model::library::v2::Error),
+    #[error("Library page error")]
+    Page(#[from] crate::model::library::versioned::Error),
+    #[error("Cookie jar error")]
+    Cookies(#[from] cookies::Error),
+    #[error("Proxy pool error")]
+    Proxy(#[from] proxy::Error),
+    #[error("Blocked by {0} page")]
+    Blocked(BlockKind),
+    #[error("Received 403 without a challenge")]
+    NoChallengeFound,
+    #[error("Still blocked after {attempts} challenge attempts")]
+    StillBlocked { attempts: usize },
+}
+
+/// An ad's library page, parsed in memory.
+#[derive(Clone, Debug)]
+pub struct AdPage {
+    /// The request and response metadata (with the page's JSON scripts as the response data).
+    pub exchange: Exchange<'static, serde_json::Value>,
+    /// Where the exchange was archived (if the client has an output directory).
+    pub path: Option<PathBuf>,
+    /// The page in whichever format was detected.
+    pub response: LibraryResponse<'static>,
+}
+
+impl AdPage {
+    /// The ad may be missing (if it isn't in the library, or the page couldn't be parsed).
+    #[must_use]
+    pub fn ad(&self) -> Option<AdSummary<'_>> {
+        self.response.ad()
+    }
+}
+
+/// A blocked request or a response with an unexpected status, archived in the failure directory
+/// (without response headers, which aren't kept for unexpected statuses).
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Failure {
+    pub status: u16,
+    /// The kind of block page (if one was detected).
+    pub block: Option<BlockKind>,
+    pub body: Option<String>,
+    /// The name of the header profile used for the request (if any).
+    pub header_profile: Option<String>,
+}
+
+pub struct SearchOptions<'a> {
+    pub params: &'a request::SearchParams,
+    /// The ID of the web UI's persisted pagination query (only the first page is fetched if missing).
+    pub doc_id: Option<&'a str>,
+    /// Maximum number of pages to fetch (all pages if missing).
+    pub limit: Option<usize>,
+    pub delay: Duration,
+}
+
+/// Options for fetching a batch of ads concurrently.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FetchOptions {
+    /// The number of ads fetched at once.
+    pub workers: usize,
+    /// The delay between requests for each worker.
+    pub delay: Duration,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            workers: 1,
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+pub struct PageOptions<'a> {
+    pub params: &'a request::PageParams,
+    /// The ID of the web UI's persisted pagination query (only the first page is fetched if missing).
+    pub doc_id: Option<&'a str>,
+    /// Maximum number of pages to fetch (all pages if missing).
+    pub limit: Option<usize>,
+    pub delay: Duration,
 }
 
 #[derive(Clone)]
 pub struct Client {
     underlying: reqwest::Client,
+    user_agent: String,
     output: Option<PathBuf>,
+    failure_output: Option<PathBuf>,
+    challenge_policy: ChallengePolicy,
+    challenge_gates: Arc<ChallengeGates>,
+    cookie_jar: Option<Arc<cookies::CookieJar>>,
+    proxy_pool: Option<Arc<proxy::ProxyPool>>,
+    header_profiles: Arc<[headers::HeaderProfile]>,
+    next_header_profile: Arc<AtomicUsize>,
+    rate_limiter: Arc<rate_limit::RateLimiter>,
+}
+
+/// The HTTP client used for a request, and the index of its proxy in the pool.
+struct Route {
+    client: reqwest::Client,
+    proxy: Option<usize>,
 }
 
 impl Client {
@@ -40,73 +147,700 @@ impl Client {
         output: Option<P>,
         user_agent: Option<S>,
     ) -> Result<Self, Error> {
-        let user_agent: Option<String> = user_agent.map(std::convert::Into::into);
-
-        let client = reqwest::ClientBuilder::new()
-            .user_agent(user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
-            .cookie_store(true);
+        let user_agent = user_agent.map_or_else(|| DEFAULT_USER_AGENT.to_string(), Into::into);
 
         Ok(Self {
-            underlying: client.build()?,
+            underlying: build_http_client(&user_agent, None, None)?,
+            user_agent,
             output: output.map(|path| path.as_ref().to_path_buf()),
+            failure_output: None,
+            challenge_policy: ChallengePolicy::default(),
+            challenge_gates: Arc::new(ChallengeGates::default()),
+            cookie_jar: None,
+            proxy_pool: None,
+            header_profiles: Arc::new([]),
+            next_header_profile: Arc::new(AtomicUsize::new(0)),
+            rate_limiter: Arc::new(rate_limit::RateLimiter::default()),
         })
     }
 
-    pub async fn app(&self, id: u64) -> Result<(), Error> {
-        let params = request::Params::new(id);
-        let request = params.build_request(None);
+    /// Archive blocked requests and unexpected statuses to a separate directory.
+    #[must_use]
+    pub fn with_failure_output<P: AsRef<Path>>(mut self, failure_output: P) -> Self {
+        self.failure_output = Some(failure_output.as_ref().to_path_buf());
+        self
+    }
+
+    /// Use a persistent cookie jar (saved after solved challenges, at most every 30 seconds after
+    /// successful responses, and when the client is dropped).
+    pub fn with_cookie_jar(mut self, cookie_jar: cookies::CookieJar) -> Result<Self, Error> {
+        let cookie_jar = Arc::new(cookie_jar);
+
+        self.underlying = build_http_client(&self.user_agent, Some(cookie_jar.clone()), None)?;
+        self.cookie_jar = Some(cookie_jar);
+
+        Ok(self)
+    }
+
+    /// Rotate through the given header profiles (one per request), which override the user agent.
+    #[must_use]
+    pub fn with_header_profiles(mut self, header_profiles: Vec<headers::HeaderProfile>) -> Self {
+        self.header_profiles = header_profiles.into();
+        self
+    }
+
+    /// Send requests through a pool of proxies, each with its own cookie jar for the profile if a
+    /// cookie directory is given.
+    pub fn with_proxy_pool(
+        mut self,
+        config: &proxy::PoolConfig,
+        cookies: Option<(&Path, &str)>,
+    ) -> Result<Self, Error> {
+        self.proxy_pool = Some(Arc::new(proxy::ProxyPool::new(
+            config,
+            &self.user_agent,
+            cookies,
+        )?));
+
+        Ok(self)
+    }
+
+    /// Limit the rate of requests to each host (shared by all clones of the client).
+    #[must_use]
+    pub fn with_rate_limit(mut self, rate_limit: rate_limit::RateLimit) -> Self {
+        self.rate_limiter = Arc::new(rate_limit::RateLimiter::new(Some(rate_limit)));
+        self
+    }
+
+    fn header_profile(&self) -> Option<&headers::HeaderProfile> {
+        if self.header_profiles.is_empty() {
+            None
+        } else {
+            let index = self.next_header_profile.fetch_add(1, Ordering::Relaxed);
+
+            self.header_profiles.get(index % self.header_profiles.len())
+        }
+    }
 
-        match scraper_trail::client::text_send(&self.underlying, request).await {
-            Ok(exchange) => {
-                let html = scraper::html::Html::parse_document(&exchange.response.data);
-                let json_scripts = html
-                    .select(&JSON_SCRIPT_SEL)
-                    .map(|element| serde_json::from_str::<serde_json::Value>(&element.inner_html()))
-                    .collect::<Result<Vec<_>, _>>()?;
+    /// Choose the HTTP client (and proxy, if there is a pool) for the next request.
+    fn route(&self) -> Result<Route, Error> {
+        match &self.proxy_pool {
+            Some(pool) => {
+                let index = pool.select()?;
+                let proxy = &pool.proxies()[index];
 
-                let exchange = exchange.map(|_| serde_json::json!(json_scripts));
+                ::log::debug!("Using proxy {}", proxy.name);
 
-                if let Some(base) = &self.output {
-                    exchange.save_file(base)?;
+                Ok(Route {
+                    client: proxy.client.clone(),
+                    proxy: Some(index),
+                })
+            }
+            None => Ok(Route {
+                client: self.underlying.clone(),
+                proxy: None,
+            }),
+        }
+    }
+
+    /// Record the result of a request, returning whether the route's proxy is now quarantined.
+    fn report(&self, route: &Route, outcome: proxy::Outcome) -> bool {
+        match (&self.proxy_pool, route.proxy) {
+            (Some(pool), Some(index)) => pool.report(index, outcome),
+            _ => false,
+        }
+    }
+
+    /// Record a failed request as blocked if a block page was detected, and as a transport failure
+    /// otherwise.
+    fn report_failure(&self, route: &Route, block: Option<BlockKind>) -> bool {
+        self.report(
+            route,
+            block.map_or(proxy::Outcome::TransportFailure, |_| {
+                proxy::Outcome::Blocked
+            }),
+        )
+    }
+
+    /// A copy of a request to archive if it fails.
+    fn failure_copy<'r>(&self, request: &Request<'r>) -> Option<Request<'r>> {
+        self.failure_output.as_ref().map(|_| request.clone())
+    }
+
+    /// Archive a failed request (if the client has a failure output directory).
+    fn save_failure(
+        &self,
+        request: Option<Request<'_>>,
+        status_code: StatusCode,
+        block: Option<BlockKind>,
+        body: Option<&str>,
+        header_profile: Option<&headers::HeaderProfile>,
+    ) -> Result<(), Error> {
+        if let (Some(base), Some(request)) = (&self.failure_output, request) {
+            let exchange = Exchange {
+                request,
+                response: scraper_trail::exchange::Response {
+                    headers: HashMap::new(),
+                    data: Failure {
+                        status: status_code.as_u16(),
+                        block,
+                        body: body.map(ToString::to_string),
+                        header_profile: header_profile
+                            .map(|header_profile| header_profile.name.to_string()),
+                    },
+                },
+            };
+
+            exchange.save_file(base)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hold all requests to the host for the policy's maximum backoff after a rate limit page.
+    fn pause_if_rate_limited(&self, host: &str, block: Option<BlockKind>) {
+        if block == Some(BlockKind::RateLimit) {
+            ::log::warn!("Rate limited by {host}; pausing requests");
+            self.rate_limiter
+                .pause(host, self.challenge_policy.max_backoff);
+        }
+    }
+
+    /// The cookie jar for the route (the proxy's own jar if it has one).
+    fn cookie_jar(&self, route: &Route) -> Option<&cookies::CookieJar> {
+        match (&self.proxy_pool, route.proxy) {
+            (Some(pool), Some(index)) => pool.proxies()[index].cookie_jar(),
+            _ => self.cookie_jar.as_deref(),
+        }
+    }
+
+    /// Save the route's cookie jar now (after a solved challenge).
+    fn save_cookies(&self, route: &Route) -> Result<(), Error> {
+        if let Some(cookie_jar) = self.cookie_jar(route) {
+            cookie_jar.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Save the route's cookie jar if it has changed and hasn't been saved recently (logging
+    /// failures, since the response is still usable and the jar is saved again on drop).
+    fn save_cookies_if_due(&self, route: &Route) {
+        if let Some(cookie_jar) = self.cookie_jar(route)
+            && let Err(error) = cookie_jar.save_if_due()
+        {
+            ::log::warn!("Failed to save cookie jar: {error:?}");
+        }
+    }
+
+    #[must_use]
+    pub const fn with_challenge_policy(mut self, challenge_policy: ChallengePolicy) -> Self {
+        self.challenge_policy = challenge_policy;
+        self
+    }
+
+    /// Fetch and parse the library page for an ad (saving it if the client has an output directory).
+    pub async fn app(&self, id: u64) -> Result<AdPage, Error> {
+        let (exchange, path) = self.json_scripts(&request::Params::new(id)).await?;
+
+        // The page keeps its JSON scripts, so the model takes its strings from a copy.
+        let response = LibraryResponse::extract_owned(&mut exchange.response.data.clone())?;
+
+        Ok(AdPage {
+            exchange,
+            path,
+            response,
+        })
+    }
+
+    /// Fetch a batch of ads concurrently, sending each result to the returned channel as it
+    /// completes.
+    ///
+    /// The workers stop once the receiver is dropped.
+    pub fn apps<I: IntoIterator<Item = u64>>(
+        &self,
+        ids: I,
+        opts: FetchOptions,
+    ) -> mpsc::Receiver<(u64, Result<AdPage, Error>)> {
+        let workers = opts.workers.max(1);
+        let ids = Arc::new(Mutex::new(ids.into_iter().collect::<Vec<_>>().into_iter()));
+        let (sender, receiver) = mpsc::channel(workers);
+
+        for _ in 0..workers {
+            let client = self.clone();
+            let ids = ids.clone();
+            let sender = sender.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    let Some(id) = ids.lock().unwrap_or_else(PoisonError::into_inner).next() else {
+                        break;
+                    };
+
+                    let result = client.app(id).await;
+
+                    if sender.send((id, result)).await.is_err() {
+                        break;
+                    }
+
+                    tokio::time::sleep(opts.delay).await;
                 }
+            });
+        }
+
+        receiver
+    }
+
+    /// Run a keyword search, following the web UI's pagination requests.
+    pub async fn search(
+        &self,
+        opts: &SearchOptions<'_>,
+    ) -> Result<Vec<SearchResults<'static>>, Error> {
+        self.paginate(
+            opts.params,
+            opts.params.clone(),
+            None,
+            opts.doc_id,
+            opts.limit,
+            opts.delay,
+        )
+        .await
+    }
+
+    /// List the ads run by a page, following the web UI's pagination requests.
+    pub async fn page(&self, opts: &PageOptions<'_>) -> Result<Vec<SearchResults<'static>>, Error> {
+        self.paginate(
+            opts.params,
+            opts.params.search_params(),
+            Some(opts.params.page_id),
+            opts.doc_id,
+            opts.limit,
+            opts.delay,
+        )
+        .await
+    }
+
+    async fn paginate<P: Params + Sync>(
+        &self,
+        initial: &P,
+        search: request::SearchParams,
+        view_all_page_id: Option<u64>,
+        doc_id: Option<&str>,
+        limit: Option<usize>,
+        delay: Duration,
+    ) -> Result<Vec<SearchResults<'static>>, Error> {
+        ::log::info!("Initial request");
 
-                Ok(())
+        let (exchange, _) = self.json_scripts(initial).await?;
+        let mut json_scripts = exchange.response.data;
+        let lsd = find_lsd_token(&json_scripts).map(str::to_string);
+
+        let Some(result) = AdLibraryResult::extract(&mut json_scripts)? else {
+            ::log::warn!(
+                "Missing ad_library_main for search: {}",
+                initial.build_request(None).url
+            );
+
+            return Ok(vec![]);
+        };
+
+        let mut pages = vec![result.into_search_results()];
+
+        while let Some(cursor) = pages.last().and_then(|page| {
+            page.page_info
+                .has_next_page
+                .then(|| page.page_info.end_cursor.to_string())
+        }) {
+            if limit.is_some_and(|limit| pages.len() >= limit) {
+                break;
             }
-            Err(scraper_trail::client::Error::UnexpectedStatus { status_code, .. })
-                if status_code == reqwest::StatusCode::FORBIDDEN =>
-            {
-                ::log::warn!("Received 403 for ad {id}; trying to acquire challenge cookies");
 
-                self.acquire_challege_cookies(id).await?;
+            let Some(doc_id) = doc_id else {
+                ::log::warn!("No pagination query ID provided; stopping after first page");
+                break;
+            };
+
+            tokio::time::sleep(delay).await;
+            ::log::info!("Pagination request: {cursor}");
+
+            let params = request::SearchPageParams {
+                search: search.clone(),
+                view_all_page_id,
+                cursor,
+                doc_id: doc_id.to_string(),
+                lsd: lsd.clone(),
+            };
+
+            let mut data = self.graphql(&params).await?;
+
+            let Some(result) = AdLibraryResult::extract(&mut data)? else {
+                ::log::warn!("Missing search results in pagination response");
+                break;
+            };
+
+            pages.push(result.into_search_results());
+        }
+
+        Ok(pages)
+    }
+
+    /// Send a GraphQL request and parse each line of the response as a JSON value.
+    async fn graphql<P: Params + Sync>(&self, params: &P) -> Result<serde_json::Value, Error> {
+        let exchange = self.send_text(params).await?;
+
+        let values = exchange
+            .response
+            .data
+            .trim_start_matches(GRAPHQL_RESPONSE_PREFIX)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let exchange = exchange.map(|_| serde_json::json!(values));
+
+        if let Some(base) = &self.output {
+            exchange.save_file(base)?;
+        }
+
+        Ok(exchange.response.data)
+    }
+
+    /// Fetch a library page and extract its JSON scripts, returning the exchange and where it was
+    /// saved.
+    async fn json_scripts<P: Params + Sync>(
+        &self,
+        params: &P,
+    ) -> Result<(Exchange<'static, serde_json::Value>, Option<PathBuf>), Error> {
+        let exchange = self.send_text(params).await?;
+
+        let html = scraper::html::Html::parse_document(&exchange.response.data);
+        let json_scripts = html
+            .select(&JSON_SCRIPT_SEL)
+            .map(|element| serde_json::from_str::<serde_json::Value>(&element.inner_html()))
+            .collect::<Result<Vec<_>, _>>()?;
 
-                Box::pin(self.app(id)).await
+        let exchange = exchange.map(|_| serde_json::json!(json_scripts));
+
+        let path = self
+            .output
+            .as_ref()
+            .map(|base| exchange.save_file(base))
+            .transpose()?;
+
+        // The response data is already owned, so only the metadata needs converting.
+        let exchange = Exchange {
+            request: exchange.request.into_static(),
+            response: scraper_trail::exchange::Response {
+                headers: exchange.response.headers.into_static(),
+                data: exchange.response.data,
+            },
+        };
+
+        Ok((exchange, path))
+    }
+
+    /// Send a request, solving challenges (up to the policy's attempt limit) and detecting block
+    /// pages.
+    async fn send_text<'a, P: Params + Sync>(
+        &self,
+        params: &'a P,
+    ) -> Result<Exchange<'a, String>, Error> {
+        let mut attempts = 0;
+        let mut route = self.route()?;
+        let header_profile = self.header_profile();
+
+        loop {
+            let mut request = params.build_request(None);
+            let url = request.url.clone();
+            let host = url.host_str().unwrap_or_default();
+
+            if let Some(header_profile) = header_profile {
+                header_profile.apply(&mut request);
+            }
+
+            let sent = self.failure_copy(&request);
+
+            self.rate_limiter.acquire(host).await;
+
+            match scraper_trail::client::text_send(&route.client, request).await {
+                Ok(exchange) => {
+                    self.save_cookies_if_due(&route);
+
+                    let block = BlockKind::detect(StatusCode::OK, &exchange.response.data);
+                    self.report(
+                        &route,
+                        block.map_or(proxy::Outcome::Success, |_| proxy::Outcome::Blocked),
+                    );
+                    self.pause_if_rate_limited(host, block);
+
+                    if let Some(kind) = block {
+                        self.save_failure(
+                            sent,
+                            StatusCode::OK,
+                            block,
+                            Some(&exchange.response.data),
+                            header_profile,
+                        )?;
+
+                        return Err(Error::Blocked(kind));
+                    }
+
+                    return Ok(exchange);
+                }
+                Err(scraper_trail::client::Error::UnexpectedStatus { status_code, body })
+                    if status_code == StatusCode::FORBIDDEN =>
+                {
+                    let body = body.unwrap_or_default();
+                    let quarantined = self.report(&route, proxy::Outcome::Blocked);
+
+                    self.save_failure(
+                        sent,
+                        status_code,
+                        BlockKind::detect(status_code, &body),
+                        Some(&body),
+                        header_profile,
+                    )?;
+
+                    if attempts == self.challenge_policy.max_attempts {
+                        return Err(Error::StillBlocked { attempts });
+                    }
+
+                    attempts += 1;
+
+                    // If another worker was handling a challenge for the host, we retry after it.
+                    let Some(gate) = self.challenge_gates.enter(host).await else {
+                        continue;
+                    };
+
+                    let outcome = self
+                        .handle_challenge(&route, &url, &body, header_profile, attempts)
+                        .await;
+
+                    drop(gate);
+
+                    match outcome? {
+                        ChallengeOutcome::Solved => {}
+                        ChallengeOutcome::NoChallengeFound => {
+                            return Err(BlockKind::detect(status_code, &body)
+                                .map_or(Error::NoChallengeFound, Error::Blocked));
+                        }
+                        ChallengeOutcome::StillBlocked => {
+                            ::log::warn!("Challenge verification rejected for {url}");
+                        }
+                    }
+
+                    // Cookies are per proxy, so we only switch if this one has been quarantined.
+                    if quarantined {
+                        route = self.route()?;
+                    }
+                }
+                Err(scraper_trail::client::Error::UnexpectedStatus { status_code, body }) => {
+                    let block = BlockKind::detect(status_code, body.as_deref().unwrap_or_default());
+
+                    // Other client errors say nothing about the proxy.
+                    if block.is_some()
+                        || status_code == StatusCode::PROXY_AUTHENTICATION_REQUIRED
+                        || status_code.is_server_error()
+                    {
+                        self.report_failure(&route, block);
+                    }
+
+                    self.pause_if_rate_limited(host, block);
+                    self.save_failure(sent, status_code, block, body.as_deref(), header_profile)?;
+
+                    return Err(block.map_or(
+                        Error::ScraperClient(scraper_trail::client::Error::UnexpectedStatus {
+                            status_code,
+                            body,
+                        }),
+                        Error::Blocked,
+                    ));
+                }
+                Err(scraper_trail::client::Error::Http(error)) => {
+                    let block = BlockKind::from_reqwest_error(&error);
+                    self.report_failure(&route, block);
+
+                    return Err(block.map_or_else(|| Error::Reqwest(error), Error::Blocked));
+                }
+                Err(error) => return Err(error.into()),
             }
-            Err(err) => Err(err.into()),
         }
     }
 
-    async fn acquire_challege_cookies(&self, id: u64) -> Result<bool, Error> {
-        let params = request::Params::new(id);
-        let request = params.build_request(None);
+    /// Pause requests to the host and solve the challenge in a 403 response (while holding the
+    /// host's challenge gate), saving the route's cookies if it's solved.
+    async fn handle_challenge(
+        &self,
+        route: &Route,
+        url: &reqwest::Url,
+        body: &str,
+        header_profile: Option<&headers::HeaderProfile>,
+        attempts: usize,
+    ) -> Result<ChallengeOutcome, Error> {
+        let host = url.host_str().unwrap_or_default();
+
+        // Other requests to the host wait while the challenge is handled.
+        self.rate_limiter
+            .pause(host, self.challenge_policy.backoff(attempts));
+
+        ::log::warn!(
+            "Received 403 for {url} (header profile: {}); attempting challenge ({attempts}/{})",
+            header_profile.map_or("none", |header_profile| &header_profile.name),
+            self.challenge_policy.max_attempts
+        );
 
-        let response = self.underlying.get(request.url.clone()).send().await?;
+        let outcome = self
+            .solve_challenge(&route.client, host, body, header_profile)
+            .await?;
 
-        match response.status() {
-            StatusCode::FORBIDDEN => {
-                let body = response.text().await?;
+        if outcome == ChallengeOutcome::Solved {
+            self.save_cookies(route)?;
+        }
+
+        Ok(outcome)
+    }
 
-                let challenge_url = CHALLENGE_URL_RE
-                    .captures(&body)
-                    .and_then(|caps| caps.get(1))
-                    .ok_or(Error::ChallengeCookies)?;
+    /// Send a challenge's verification request (rate limited like other requests to the host, and
+    /// with the same header profile as the blocked request).
+    async fn solve_challenge(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        body: &str,
+        header_profile: Option<&headers::HeaderProfile>,
+    ) -> Result<ChallengeOutcome, Error> {
+        let Some(path) = challenge::challenge_path(body) else {
+            return Ok(ChallengeOutcome::NoChallengeFound);
+        };
 
-                let url = format!("https://www.facebook.com{}", challenge_url.as_str());
+        let url = format!("https://{host}{path}");
+        let mut builder = client.post(&url);
 
-                self.underlying.post(&url).send().await?;
+        if let Some(header_profile) = header_profile {
+            builder = builder.header(
+                reqwest::header::USER_AGENT,
+                header_profile.user_agent.as_ref(),
+            );
 
-                Ok(true)
+            for (name, value) in &header_profile.headers {
+                builder = builder.header(name.as_ref(), value.as_ref());
             }
-            _ => Ok(false),
         }
+
+        self.rate_limiter.acquire(host).await;
+
+        let response = builder.send().await?;
+
+        Ok(if response.status().is_success() {
+            ChallengeOutcome::Solved
+        } else {
+            ChallengeOutcome::StillBlocked
+        })
+    }
+}
+
+pub(crate) fn build_http_client(
+    user_agent: &str,
+    cookie_jar: Option<Arc<cookies::CookieJar>>,
+    proxy: Option<reqwest::Proxy>,
+) -> Result<reqwest::Client, reqwest::Error> {
+    let builder = reqwest::ClientBuilder::new()
+        .user_agent(user_agent)
+        .redirect(challenge::redirect_policy());
+
+    let builder = match cookie_jar {
+        Some(cookie_jar) => builder.cookie_provider(cookie_jar),
+        None => builder.cookie_store(true),
+    };
+
+    let builder = match proxy {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    };
+
+    builder.build()
+}
+
+/// Find the anti-CSRF token that the web UI sends with its GraphQL requests.
+///
+/// The token appears in the page's JSON scripts as a module definition of the form
+/// `["LSD", [], {"token": "..."}, ...]`.
+fn find_lsd_token(value: &serde_json::Value) -> Option<&str> {
+    match value {
+        serde_json::Value::Array(values) => {
+            if values.first().and_then(serde_json::Value::as_str) == Some("LSD")
+                && let Some(token) = values
+                    .get(2)
+                    .and_then(|config| config.get("token"))
+                    .and_then(serde_json::Value::as_str)
+            {
+                return Some(token);
+            }
+
+            values.iter().find_map(find_lsd_token)
+        }
+        serde_json::Value::Object(fields) => fields.values().find_map(find_lsd_token),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_lsd_token_in_json_scripts() {
+        let value = serde_json::json!([{
+            "require": [["ScheduledServerJS", "handle", null, [{
+                "define": [
+                    ["SiteData", [], {"lsd": false}, 317],
+                    ["LSD", [], {"token": "AVqbxe3J_zU"}, 323]
+                ]
+            }]]]
+        }]);
+
+        assert_eq!(find_lsd_token(&value), Some("AVqbxe3J_zU"));
+        assert_eq!(find_lsd_token(&serde_json::json!(["LSD"])), None);
+    }
+
+    #[test]
+    fn save_failure_with_header_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let base =
+            std::env::temp_dir().join(format!("meta-ads-access-failures-{}", std::process::id()));
+        let header_profile = headers::HeaderProfile::find("firefox-linux").ok_or("missing")?;
+        let client = Client::new(None::<&Path>, None::<&str>)?.with_failure_output(&base);
+
+        let params = request::Params::new(1);
+        let mut request = params.build_request(None);
+        header_profile.apply(&mut request);
+
+        let sent = client.failure_copy(&request);
+        client.save_failure(
+            sent,
+            StatusCode::FORBIDDEN,
+            Some(BlockKind::LoginWall),
+            Some("<form id=\"login_form\">"),
+            Some(header_profile),
+        )?;
+
+        let path = std::fs::read_dir(&base)?
+            .next()
+            .ok_or("missing failure")??
+            .path();
+        let contents = std::fs::read_to_string(path)?;
+        let exchange = serde_json::from_str::<Exchange<'_, Failure>>(&contents)?;
+
+        assert_eq!(exchange.request.headers, request.headers);
+        assert_eq!(
+            exchange.response.data.header_profile.as_deref(),
+            Some("firefox-linux")
+        );
+        assert_eq!(exchange.response.data.status, 403);
+        assert_eq!(exchange.response.data.block, Some(BlockKind::LoginWall));
+
+        std::fs::remove_dir_all(base)?;
+
+        Ok(())
     }
 }
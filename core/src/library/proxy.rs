@@ -0,0 +1,449 @@
+//! A pool of HTTP and SOCKS proxies with rotation and quarantine of blocked proxies.
+
+use super::cookies::CookieJar;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const DEFAULT_ROTATE_EVERY: usize = 1;
+const DEFAULT_MAX_FAILURES: usize = 3;
+const DEFAULT_QUARANTINE_SECONDS: u64 = 600;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("TOML error")]
+    Toml(#[from] toml::de::Error),
+    #[error("HTTP client error")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Cookie jar error")]
+    Cookies(#[from] super::cookies::Error),
+    #[error("No proxies configured")]
+    Empty,
+    #[error("All proxies are quarantined")]
+    AllQuarantined,
+}
+
+/// A single proxy in the pool configuration.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+pub struct ProxyConfig {
+    /// An `http`, `https`, `socks5`, or `socks5h` URL (credentials may be included).
+    pub url: String,
+    /// Used in logging and to name the proxy's cookie jar (defaults to `proxy-<index>`).
+    pub name: Option<String>,
+}
+
+/// The pool configuration, usually loaded from a TOML file.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+pub struct PoolConfig {
+    /// The number of requests to send through a proxy before moving to the next one.
+    #[serde(default = "default_rotate_every")]
+    pub rotate_every: usize,
+    /// The number of consecutive blocked responses or transport failures after which a proxy is
+    /// quarantined.
+    #[serde(default = "default_max_failures")]
+    pub max_failures: usize,
+    #[serde(default = "default_quarantine_seconds")]
+    pub quarantine_seconds: u64,
+    pub proxies: Vec<ProxyConfig>,
+}
+
+const fn default_rotate_every() -> usize {
+    DEFAULT_ROTATE_EVERY
+}
+
+const fn default_max_failures() -> usize {
+    DEFAULT_MAX_FAILURES
+}
+
+const fn default_quarantine_seconds() -> u64 {
+    DEFAULT_QUARANTINE_SECONDS
+}
+
+impl PoolConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// The result of a request sent through a proxy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Success,
+    /// A 403, challenge, or block page.
+    Blocked,
+    /// A connection error, a proxy authentication failure, or a server error.
+    TransportFailure,
+}
+
+/// A proxy with its own HTTP client and (optionally) its own persistent cookie jar.
+#[derive(Clone, Debug)]
+pub struct Proxy {
+    pub name: String,
+    pub(super) client: reqwest::Client,
+    pub(super) cookie_jar: Option<Arc<CookieJar>>,
+}
+
+impl Proxy {
+    #[must_use]
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_deref()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Health {
+    consecutive_failures: usize,
+    quarantined_until: Option<Instant>,
+}
+
+impl Health {
+    fn is_quarantined(&self, now: Instant) -> bool {
+        self.quarantined_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    current: usize,
+    served: usize,
+    health: Vec<Health>,
+}
+
+#[derive(Debug)]
+pub struct ProxyPool {
+    proxies: Vec<Proxy>,
+    rotate_every: usize,
+    max_failures: usize,
+    quarantine: Duration,
+    state: Mutex<State>,
+}
+
+impl ProxyPool {
+    /// Build a client for each proxy, loading per-proxy cookie jars for the profile if a cookie
+    /// directory is given.
+    pub fn new(
+        config: &PoolConfig,
+        user_agent: &str,
+        cookies: Option<(&Path, &str)>,
+    ) -> Result<Self, Error> {
+        if config.proxies.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let proxies = config
+            .proxies
+            .iter()
+            .enumerate()
+            .map(|(index, proxy)| {
+                let name = proxy
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("proxy-{index}"));
+
+                let cookie_jar = cookies
+                    .map(|(dir, profile)| {
+                        CookieJar::load_profile(dir, &format!("{profile}-{name}")).map(Arc::new)
+                    })
+                    .transpose()?;
+
+                let client = super::build_http_client(
+                    user_agent,
+                    cookie_jar.clone(),
+                    Some(reqwest::Proxy::all(&proxy.url)?),
+                )?;
+
+                Ok(Proxy {
+                    name,
+                    client,
+                    cookie_jar,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self {
+            state: Mutex::new(State {
+                current: 0,
+                served: 0,
+                health: vec![Health::default(); proxies.len()],
+            }),
+            proxies,
+            rotate_every: config.rotate_every.max(1),
+            max_failures: config.max_failures.max(1),
+            quarantine: Duration::from_secs(config.quarantine_seconds),
+        })
+    }
+
+    #[must_use]
+    pub fn proxies(&self) -> &[Proxy] {
+        &self.proxies
+    }
+
+    /// Choose the proxy for the next request.
+    pub fn select(&self) -> Result<usize, Error> {
+        self.select_at(Instant::now())
+    }
+
+    /// Record a successful response, clearing the proxy's failure count.
+    pub fn report_success(&self, index: usize) {
+        let mut state = self.state();
+
+        if let Some(health) = state.health.get_mut(index) {
+            health.consecutive_failures = 0;
+        }
+    }
+
+    /// Record the result of a request, returning whether the proxy is now quarantined.
+    pub fn report(&self, index: usize, outcome: Outcome) -> bool {
+        match outcome {
+            Outcome::Success => {
+                self.report_success(index);
+                false
+            }
+            Outcome::Blocked => self.report_failure(index),
+            Outcome::TransportFailure => {
+                if let Some(proxy) = self.proxies.get(index) {
+                    ::log::warn!("Transport failure through proxy {}", proxy.name);
+                }
+
+                self.report_failure(index)
+            }
+        }
+    }
+
+    /// Record a blocked request or transport failure, returning whether the proxy is now
+    /// quarantined.
+    pub fn report_failure(&self, index: usize) -> bool {
+        self.report_failure_at(index, Instant::now())
+    }
+
+    #[must_use]
+    pub fn is_quarantined(&self, index: usize) -> bool {
+        self.state()
+            .health
+            .get(index)
+            .is_some_and(|health| health.is_quarantined(Instant::now()))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn select_at(&self, now: Instant) -> Result<usize, Error> {
+        let mut state = self.state();
+        let len = state.health.len();
+
+        let rotate =
+            state.served >= self.rotate_every || state.health[state.current].is_quarantined(now);
+        let start = if rotate {
+            state.current + 1
+        } else {
+            state.current
+        };
+
+        let index = (start..start + len)
+            .map(|index| index % len)
+            .find(|index| !state.health[*index].is_quarantined(now))
+            .ok_or(Error::AllQuarantined)?;
+
+        if index != state.current || rotate {
+            state.served = 0;
+        }
+
+        state.current = index;
+        state.served += 1;
+        drop(state);
+
+        Ok(index)
+    }
+
+    fn report_failure_at(&self, index: usize, now: Instant) -> bool {
+        let mut state = self.state();
+
+        let Some(health) = state.health.get_mut(index) else {
+            return false;
+        };
+
+        health.consecutive_failures += 1;
+
+        let quarantined = health.consecutive_failures >= self.max_failures;
+
+        if quarantined {
+            health.consecutive_failures = 0;
+            health.quarantined_until = Some(now + self.quarantine);
+        }
+
+        drop(state);
+
+        if quarantined {
+            ::log::warn!("Quarantining proxy {}", self.proxies[index].name);
+        }
+
+        quarantined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    fn config(urls: &[&str], rotate_every: usize) -> PoolConfig {
+        PoolConfig {
+            rotate_every,
+            max_failures: 2,
+            quarantine_seconds: 60,
+            proxies: urls
+                .iter()
+                .map(|url| ProxyConfig {
+                    url: (*url).to_string(),
+                    name: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// A stand-in proxy that answers every request with the given status and its name as body.
+    fn stand_in_proxy(status: &'static str, name: &'static str) -> std::io::Result<String> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+
+                while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                    line.clear();
+                }
+
+                let _ = write!(
+                    &stream,
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{name}",
+                    name.len()
+                );
+            }
+        });
+
+        Ok(url)
+    }
+
+    /// A stand-in proxy that closes every connection without answering.
+    fn closing_proxy() -> std::io::Result<String> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                drop(stream);
+            }
+        });
+
+        Ok(url)
+    }
+
+    #[test]
+    fn rotate_every_n_requests() -> Result<(), Error> {
+        let pool = ProxyPool::new(
+            &config(&["http://127.0.0.1:1", "http://127.0.0.1:2"], 2),
+            "test",
+            None,
+        )?;
+        let now = Instant::now();
+
+        let selected = (0..5)
+            .map(|_| pool.select_at(now))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(selected, vec![0, 0, 1, 1, 0]);
+        assert_eq!(pool.proxies()[1].name, "proxy-1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn quarantine_and_recover() -> Result<(), Error> {
+        let pool = ProxyPool::new(
+            &config(&["socks5h://127.0.0.1:1", "http://127.0.0.1:2"], 10),
+            "test",
+            None,
+        )?;
+        let now = Instant::now();
+
+        assert_eq!(pool.select_at(now)?, 0);
+        assert!(!pool.report_failure_at(0, now));
+        pool.report_success(0);
+        assert!(!pool.report_failure_at(0, now));
+        assert!(pool.report_failure_at(0, now));
+        assert_eq!(pool.select_at(now)?, 1);
+        assert_eq!(pool.select_at(now)?, 1);
+
+        pool.report_failure_at(1, now);
+        assert!(pool.report_failure_at(1, now));
+        assert!(matches!(pool.select_at(now), Err(Error::AllQuarantined)));
+
+        assert_eq!(pool.select_at(now + Duration::from_secs(61))?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn route_through_stand_in_proxies() -> Result<(), Box<dyn std::error::Error>> {
+        let blocked = stand_in_proxy("403 Forbidden", "blocked")?;
+        let open = stand_in_proxy("200 OK", "open")?;
+        let pool = ProxyPool::new(&config(&[&blocked, &open], 1), "test", None)?;
+
+        let mut bodies = vec![];
+
+        for _ in 0..3 {
+            let index = pool.select()?;
+            let response = pool.proxies()[index]
+                .client
+                .get("http://ads.example.test/")
+                .send()
+                .await?;
+
+            if response.status() == reqwest::StatusCode::FORBIDDEN {
+                pool.report_failure(index);
+            } else {
+                pool.report_success(index);
+            }
+
+            bodies.push(response.text().await?);
+        }
+
+        assert_eq!(bodies, vec!["blocked", "open", "blocked"]);
+
+        pool.report_failure(0);
+        assert!(pool.is_quarantined(0));
+        assert_eq!(pool.select()?, 1);
+        assert_eq!(pool.select()?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quarantine_after_transport_failures() -> Result<(), Box<dyn std::error::Error>> {
+        let closing = closing_proxy()?;
+        let client = super::super::Client::new(None::<&Path>, None::<&str>)?
+            .with_proxy_pool(&config(&[&closing], 1), None)?;
+
+        for _ in 0..2 {
+            assert!(matches!(
+                client.app(1).await,
+                Err(super::super::Error::Reqwest(_))
+            ));
+        }
+
+        let pool = client.proxy_pool.as_ref().ok_or("missing proxy pool")?;
+
+        assert!(pool.is_quarantined(0));
+        assert!(matches!(
+            client.app(1).await,
+            Err(super::super::Error::Proxy(Error::AllQuarantined))
+        ));
+
+        Ok(())
+    }
+}
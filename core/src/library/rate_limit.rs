@@ -0,0 +1,179 @@
+//! Per-host token-bucket rate limiting, with pauses while a blocked request is handled.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// The maximum request rate for a single host.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests per second allowed on average.
+    pub per_second: f64,
+    /// The number of requests that can be sent at once after an idle period.
+    pub burst: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    paused_until: Option<Instant>,
+}
+
+/// Rate limits shared by all clones of a library client (and therefore all of its workers).
+///
+/// Hosts are paused even if no rate limit is configured.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limit: Option<RateLimit>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(limit: Option<RateLimit>) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn limit(&self) -> Option<RateLimit> {
+        self.limit
+    }
+
+    /// Wait until a request to the host is allowed.
+    pub async fn acquire(&self, host: &str) {
+        while let Some(wait) = self.try_acquire_at(host, Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold all requests to the host for the given duration (or longer, if it is already paused).
+    pub fn pause(&self, host: &str, duration: Duration) {
+        self.pause_at(host, duration, Instant::now());
+    }
+
+    fn bucket_at<'a>(
+        &self,
+        buckets: &'a mut HashMap<String, Bucket>,
+        host: &str,
+        now: Instant,
+    ) -> &'a mut Bucket {
+        let burst = self
+            .limit
+            .map_or(0.0, |limit| f64::from(limit.burst.max(1)));
+
+        buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+            paused_until: None,
+        })
+    }
+
+    /// Take a token if one is available, or return how long to wait before trying again.
+    fn try_acquire_at(&self, host: &str, now: Instant) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let wait = self
+            .bucket_at(&mut buckets, host, now)
+            .try_take(self.limit, now);
+        drop(buckets);
+
+        wait
+    }
+
+    fn pause_at(&self, host: &str, duration: Duration, now: Instant) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        self.bucket_at(&mut buckets, host, now)
+            .pause_until(now + duration);
+        drop(buckets);
+    }
+}
+
+impl Bucket {
+    fn try_take(&mut self, limit: Option<RateLimit>, now: Instant) -> Option<Duration> {
+        if let Some(paused_until) = self.paused_until {
+            if paused_until > now {
+                return Some(paused_until - now);
+            }
+
+            self.paused_until = None;
+        }
+
+        let limit = limit?;
+        let burst = f64::from(limit.burst.max(1));
+
+        self.tokens = now
+            .saturating_duration_since(self.updated)
+            .as_secs_f64()
+            .mul_add(limit.per_second, self.tokens)
+            .min(burst);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / limit.per_second,
+            ))
+        }
+    }
+
+    fn pause_until(&mut self, until: Instant) {
+        self.paused_until = Some(
+            self.paused_until
+                .map_or(until, |current| current.max(until)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "www.facebook.com";
+
+    #[test]
+    fn token_bucket_per_host() {
+        let limiter = RateLimiter::new(Some(RateLimit {
+            per_second: 2.0,
+            burst: 2,
+        }));
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire_at(HOST, now), None);
+        assert_eq!(limiter.try_acquire_at(HOST, now), None);
+        assert_eq!(
+            limiter.try_acquire_at(HOST, now),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(limiter.try_acquire_at("example.com", now), None);
+        assert_eq!(
+            limiter.try_acquire_at(HOST, now + Duration::from_millis(500)),
+            None
+        );
+    }
+
+    #[test]
+    fn pause_holds_requests() {
+        let limiter = RateLimiter::new(None);
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire_at(HOST, now), None);
+
+        limiter.pause_at(HOST, Duration::from_secs(4), now);
+        limiter.pause_at(HOST, Duration::from_secs(1), now);
+
+        assert_eq!(
+            limiter.try_acquire_at(HOST, now + Duration::from_secs(1)),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(limiter.try_acquire_at("example.com", now), None);
+        assert_eq!(
+            limiter.try_acquire_at(HOST, now + Duration::from_secs(4)),
+            None
+        );
+    }
+}
@@ -1,7 +1,20 @@
+use crate::model::country::CountryCode;
 use chrono::{DateTime, Utc};
 use scraper_trail::request::{Request, params::ParseError};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::LazyLock;
 
 const DOMAIN: &str = "www.facebook.com";
+const ALL_COUNTRIES: &str = "ALL";
+const GRAPHQL_URL: &str = "https://www.facebook.com/api/graphql/";
+const SEARCH_PAGINATION_QUERY_NAME: &str = "AdLibrarySearchPaginationQuery";
+const EXPECTED_PAGINATION_URL_MESSAGE: &str = "Facebook GraphQL URL";
+const EXPECTED_PAGINATION_BODY_MESSAGE: &str = "Ads Library search pagination query";
+const PAGE_SIZE: usize = 30;
+
+static SEARCH_BASE_URL: LazyLock<url::Url> =
+    LazyLock::new(|| url::Url::parse("https://www.facebook.com/ads/library/").unwrap());
 const EXPECTED_URL_MESSAGE: &str = "Facebook Ads Library URL";
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -48,3 +61,702 @@ impl scraper_trail::request::params::Params for Params {
         .unwrap()
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid search option")]
+    InvalidSearchOption(String),
+}
+
+/// The category of ads to search.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AdType {
+    #[default]
+    All,
+    PoliticalAndIssueAds,
+    HousingAds,
+    EmploymentAds,
+    CreditAds,
+}
+
+impl AdType {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::PoliticalAndIssueAds => "political_and_issue_ads",
+            Self::HousingAds => "housing_ads",
+            Self::EmploymentAds => "employment_ads",
+            Self::CreditAds => "credit_ads",
+        }
+    }
+}
+
+impl Display for AdType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AdType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "political_and_issue_ads" => Ok(Self::PoliticalAndIssueAds),
+            "housing_ads" => Ok(Self::HousingAds),
+            "employment_ads" => Ok(Self::EmploymentAds),
+            "credit_ads" => Ok(Self::CreditAds),
+            other => Err(Error::InvalidSearchOption(other.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ActiveStatus {
+    Active,
+    Inactive,
+    #[default]
+    All,
+}
+
+impl ActiveStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Inactive => "inactive",
+            Self::All => "all",
+        }
+    }
+}
+
+impl Display for ActiveStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ActiveStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(Self::Active),
+            "inactive" => Ok(Self::Inactive),
+            "all" => Ok(Self::All),
+            other => Err(Error::InvalidSearchOption(other.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum MediaType {
+    #[default]
+    All,
+    Image,
+    Meme,
+    Video,
+    None,
+}
+
+impl MediaType {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Image => "image",
+            Self::Meme => "meme",
+            Self::Video => "video",
+            Self::None => "none",
+        }
+    }
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "image" => Ok(Self::Image),
+            "meme" => Ok(Self::Meme),
+            "video" => Ok(Self::Video),
+            "none" => Ok(Self::None),
+            other => Err(Error::InvalidSearchOption(other.to_string())),
+        }
+    }
+}
+
+/// How the query is matched (or whether it identifies a page).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SearchType {
+    #[default]
+    KeywordUnordered,
+    KeywordExactPhrase,
+    Page,
+}
+
+impl SearchType {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::KeywordUnordered => "keyword_unordered",
+            Self::KeywordExactPhrase => "keyword_exact_phrase",
+            Self::Page => "page",
+        }
+    }
+}
+
+impl Display for SearchType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SearchType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyword_unordered" => Ok(Self::KeywordUnordered),
+            "keyword_exact_phrase" => Ok(Self::KeywordExactPhrase),
+            "page" => Ok(Self::Page),
+            other => Err(Error::InvalidSearchOption(other.to_string())),
+        }
+    }
+}
+
+/// A keyword search on the Ads Library web interface.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchParams {
+    pub query: String,
+    /// All countries if missing.
+    pub country: Option<CountryCode>,
+    pub ad_type: AdType,
+    pub active_status: ActiveStatus,
+    pub media_type: MediaType,
+    pub search_type: SearchType,
+}
+
+impl SearchParams {
+    #[must_use]
+    pub fn new<S: Into<String>>(query: S) -> Self {
+        Self {
+            query: query.into(),
+            country: None,
+            ad_type: AdType::default(),
+            active_status: ActiveStatus::default(),
+            media_type: MediaType::default(),
+            search_type: SearchType::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn parse_url(url: &url::Url) -> Option<Self> {
+        if url.scheme() == "https" && url.domain() == Some(DOMAIN) && url.path() == "/ads/library/"
+        {
+            let mut params = Self::new(String::new());
+            let mut query = None;
+
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "q" => query = Some(value.into_owned()),
+                    "country" => {
+                        params.country = if value == ALL_COUNTRIES {
+                            None
+                        } else {
+                            Some(value.parse().ok()?)
+                        };
+                    }
+                    "ad_type" => params.ad_type = value.parse().ok()?,
+                    "active_status" => params.active_status = value.parse().ok()?,
+                    "media_type" => params.media_type = value.parse().ok()?,
+                    "search_type" => params.search_type = value.parse().ok()?,
+                    "id" | "view_all_page_id" => return None,
+                    _ => {}
+                }
+            }
+
+            params.query = query?;
+
+            Some(params)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn url(&self) -> url::Url {
+        let mut url = SEARCH_BASE_URL.clone();
+
+        url.query_pairs_mut()
+            .append_pair("active_status", self.active_status.as_str())
+            .append_pair("ad_type", self.ad_type.as_str())
+            .append_pair(
+                "country",
+                self.country.map_or(ALL_COUNTRIES, CountryCode::as_str),
+            )
+            .append_pair("is_targeted_country", "false")
+            .append_pair("media_type", self.media_type.as_str())
+            .append_pair("q", &self.query)
+            .append_pair("search_type", self.search_type.as_str());
+
+        url
+    }
+}
+
+impl scraper_trail::request::params::Params for SearchParams {
+    fn parse_request(request: &Request<'_>) -> Result<Self, ParseError> {
+        Self::parse_url(&request.url).ok_or(Params::error())
+    }
+
+    fn build_request(&self, timestamp: Option<DateTime<Utc>>) -> Request<'_> {
+        Request::new::<_, String, String, Vec<(String, String)>, String>(
+            self.url().as_str(),
+            timestamp,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+}
+
+/// All ads run by a single page on the Ads Library web interface.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PageParams {
+    pub page_id: u64,
+    /// All countries if missing.
+    pub country: Option<CountryCode>,
+    pub ad_type: AdType,
+    pub active_status: ActiveStatus,
+    pub media_type: MediaType,
+}
+
+impl PageParams {
+    #[must_use]
+    pub fn new(page_id: u64) -> Self {
+        Self {
+            page_id,
+            country: None,
+            ad_type: AdType::default(),
+            active_status: ActiveStatus::default(),
+            media_type: MediaType::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn parse_url(url: &url::Url) -> Option<Self> {
+        if url.scheme() == "https" && url.domain() == Some(DOMAIN) && url.path() == "/ads/library/"
+        {
+            let mut params = Self::new(0);
+            let mut page_id = None;
+
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "view_all_page_id" => page_id = Some(value.parse().ok()?),
+                    "country" => {
+                        params.country = if value == ALL_COUNTRIES {
+                            None
+                        } else {
+                            Some(value.parse().ok()?)
+                        };
+                    }
+                    "ad_type" => params.ad_type = value.parse().ok()?,
+                    "active_status" => params.active_status = value.parse().ok()?,
+                    "media_type" => params.media_type = value.parse().ok()?,
+                    "id" => return None,
+                    _ => {}
+                }
+            }
+
+            params.page_id = page_id?;
+
+            Some(params)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn url(&self) -> url::Url {
+        let mut url = SEARCH_BASE_URL.clone();
+
+        url.query_pairs_mut()
+            .append_pair("active_status", self.active_status.as_str())
+            .append_pair("ad_type", self.ad_type.as_str())
+            .append_pair(
+                "country",
+                self.country.map_or(ALL_COUNTRIES, CountryCode::as_str),
+            )
+            .append_pair("is_targeted_country", "false")
+            .append_pair("media_type", self.media_type.as_str())
+            .append_pair("search_type", SearchType::Page.as_str())
+            .append_pair("view_all_page_id", &self.page_id.to_string());
+
+        url
+    }
+
+    /// The equivalent search filters (used for pagination requests).
+    #[must_use]
+    pub const fn search_params(&self) -> SearchParams {
+        SearchParams {
+            query: String::new(),
+            country: self.country,
+            ad_type: self.ad_type,
+            active_status: self.active_status,
+            media_type: self.media_type,
+            search_type: SearchType::Page,
+        }
+    }
+}
+
+impl scraper_trail::request::params::Params for PageParams {
+    fn parse_request(request: &Request<'_>) -> Result<Self, ParseError> {
+        Self::parse_url(&request.url).ok_or(Params::error())
+    }
+
+    fn build_request(&self, timestamp: Option<DateTime<Utc>>) -> Request<'_> {
+        Request::new::<_, String, String, Vec<(String, String)>, String>(
+            self.url().as_str(),
+            timestamp,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+}
+
+/// A follow-up request for a further page of library search results, as issued by the web UI.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchPageParams {
+    pub search: SearchParams,
+    /// Set when paging through all ads for a page.
+    pub view_all_page_id: Option<u64>,
+    /// The `end_cursor` of the previous page.
+    pub cursor: String,
+    /// The ID of the web UI's persisted pagination query.
+    pub doc_id: String,
+    /// The anti-CSRF token embedded in the initial search page.
+    pub lsd: Option<String>,
+}
+
+impl SearchPageParams {
+    const fn error() -> ParseError {
+        ParseError::InvalidBody {
+            expected: EXPECTED_PAGINATION_BODY_MESSAGE,
+        }
+    }
+
+    fn variables(&self) -> serde_json::Value {
+        serde_json::json!({
+            "activeStatus": self.search.active_status.as_str().to_ascii_uppercase(),
+            "adType": self.search.ad_type.as_str().to_ascii_uppercase(),
+            "bylines": [],
+            "collationToken": null,
+            "contentLanguages": [],
+            "countries": [self.search.country.map_or(ALL_COUNTRIES, CountryCode::as_str)],
+            "cursor": self.cursor,
+            "excludedIDs": null,
+            "first": PAGE_SIZE,
+            "isTargetedCountry": false,
+            "location": null,
+            "mediaType": self.search.media_type.as_str().to_ascii_uppercase(),
+            "multiCountryFilterMode": null,
+            "pageIDs": [],
+            "potentialReachInput": null,
+            "publisherPlatforms": [],
+            "queryString": self.search.query,
+            "regions": null,
+            "searchType": self.search.search_type.as_str().to_ascii_uppercase(),
+            "sortData": null,
+            "source": null,
+            "startDate": null,
+            "viewAllPageID": self.view_all_page_id.map_or_else(|| "0".to_string(), |id| id.to_string())
+        })
+    }
+
+    /// The URL of the library page that the results were first loaded from.
+    #[must_use]
+    pub fn initial_url(&self) -> url::Url {
+        self.view_all_page_id.map_or_else(
+            || self.search.url(),
+            |page_id| {
+                PageParams {
+                    page_id,
+                    country: self.search.country,
+                    ad_type: self.search.ad_type,
+                    active_status: self.search.active_status,
+                    media_type: self.search.media_type,
+                }
+                .url()
+            },
+        )
+    }
+
+    fn parse_variables(
+        variables: &serde_json::Value,
+    ) -> Option<(SearchParams, Option<u64>, String)> {
+        let field = |name: &str| variables.get(name).and_then(serde_json::Value::as_str);
+        let option = |name: &str| field(name).map(str::to_ascii_lowercase);
+
+        let country = match variables.get("countries")?.as_array()?.as_slice() {
+            [] => None,
+            [country] if country.as_str()? == ALL_COUNTRIES => None,
+            [country] => Some(country.as_str()?.parse().ok()?),
+            _ => return None,
+        };
+
+        Some((
+            SearchParams {
+                query: field("queryString")?.to_string(),
+                country,
+                ad_type: option("adType")?.parse().ok()?,
+                active_status: option("activeStatus")?.parse().ok()?,
+                media_type: option("mediaType")?.parse().ok()?,
+                search_type: option("searchType")?.parse().ok()?,
+            },
+            match field("viewAllPageID") {
+                None | Some("0") => None,
+                Some(page_id) => Some(page_id.parse().ok()?),
+            },
+            field("cursor")?.to_string(),
+        ))
+    }
+}
+
+impl scraper_trail::request::params::Params for SearchPageParams {
+    fn parse_request(request: &Request<'_>) -> Result<Self, ParseError> {
+        if request.url.as_str() != GRAPHQL_URL {
+            return Err(ParseError::InvalidUrl {
+                expected: EXPECTED_PAGINATION_URL_MESSAGE,
+            });
+        }
+
+        let body = request.body.as_deref().ok_or(Self::error())?;
+
+        let mut friendly_name = None;
+        let mut doc_id = None;
+        let mut lsd = None;
+        let mut variables = None;
+
+        for (key, value) in url::form_urlencoded::parse(body.as_bytes()) {
+            match key.as_ref() {
+                "fb_api_req_friendly_name" => friendly_name = Some(value),
+                "doc_id" => doc_id = Some(value.into_owned()),
+                "lsd" => lsd = Some(value.into_owned()),
+                "variables" => variables = serde_json::from_str(&value).ok(),
+                _ => {}
+            }
+        }
+
+        if friendly_name.as_deref() != Some(SEARCH_PAGINATION_QUERY_NAME) {
+            return Err(Self::error());
+        }
+
+        let (search, view_all_page_id, cursor) = variables
+            .as_ref()
+            .and_then(Self::parse_variables)
+            .ok_or(Self::error())?;
+
+        Ok(Self {
+            search,
+            view_all_page_id,
+            cursor,
+            doc_id: doc_id.ok_or(Self::error())?,
+            lsd,
+        })
+    }
+
+    fn build_request(&self, timestamp: Option<DateTime<Utc>>) -> Request<'_> {
+        let mut body = url::form_urlencoded::Serializer::new(String::new());
+
+        body.append_pair("av", "0").append_pair("__a", "1");
+
+        if let Some(lsd) = &self.lsd {
+            body.append_pair("lsd", lsd);
+        }
+
+        body.append_pair("fb_api_caller_class", "RelayModern")
+            .append_pair("fb_api_req_friendly_name", SEARCH_PAGINATION_QUERY_NAME)
+            .append_pair("variables", &self.variables().to_string())
+            .append_pair("server_timestamps", "true")
+            .append_pair("doc_id", &self.doc_id);
+
+        let mut headers = vec![
+            ("content-type", "application/x-www-form-urlencoded"),
+            ("x-fb-friendly-name", SEARCH_PAGINATION_QUERY_NAME),
+        ];
+
+        if let Some(lsd) = &self.lsd {
+            headers.push(("x-fb-lsd", lsd));
+        }
+
+        Request::new(
+            GRAPHQL_URL,
+            timestamp,
+            Some(reqwest::Method::POST),
+            Some(headers),
+            Some(body.finish()),
+        )
+        .unwrap()
+    }
+}
+
+/// An initial library search or page listing, or a follow-up pagination request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SearchRequestParams {
+    Initial(SearchParams),
+    ViewAllPage(PageParams),
+    Page(SearchPageParams),
+}
+
+impl SearchRequestParams {
+    /// The URL of the library page that the results were first loaded from.
+    #[must_use]
+    pub fn initial_url(&self) -> url::Url {
+        match self {
+            Self::Initial(search) => search.url(),
+            Self::ViewAllPage(page) => page.url(),
+            Self::Page(page) => page.initial_url(),
+        }
+    }
+}
+
+impl scraper_trail::request::params::Params for SearchRequestParams {
+    fn parse_request(request: &Request<'_>) -> Result<Self, ParseError> {
+        SearchParams::parse_request(request)
+            .map(Self::Initial)
+            .or_else(|_| PageParams::parse_request(request).map(Self::ViewAllPage))
+            .or_else(|_| SearchPageParams::parse_request(request).map(Self::Page))
+    }
+
+    fn build_request(&self, timestamp: Option<DateTime<Utc>>) -> Request<'_> {
+        match self {
+            Self::Initial(search) => search.build_request(timestamp),
+            Self::ViewAllPage(page) => page.build_request(timestamp),
+            Self::Page(page) => page.build_request(timestamp),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_url_roundtrip() {
+        let params = SearchParams {
+            country: Some("DE".parse().unwrap()),
+            search_type: SearchType::KeywordExactPhrase,
+            ..SearchParams::new("chess & go")
+        };
+
+        let url = params.url();
+
+        assert_eq!(
+            url.as_str(),
+            "https://www.facebook.com/ads/library/?active_status=all&ad_type=all&country=DE&is_targeted_country=false&media_type=all&q=chess+%26+go&search_type=keyword_exact_phrase"
+        );
+        assert_eq!(SearchParams::parse_url(&url), Some(params));
+    }
+
+    #[test]
+    fn parse_browser_search_url() {
+        let url = "https://www.facebook.com/ads/library/?active_status=active&ad_type=political_and_issue_ads&country=ALL&media_type=video&q=chess&sort_data[direction]=desc"
+            .parse()
+            .unwrap();
+
+        let params = SearchParams::parse_url(&url).unwrap();
+
+        assert_eq!(params.query, "chess");
+        assert_eq!(params.country, None);
+        assert_eq!(params.ad_type, AdType::PoliticalAndIssueAds);
+        assert_eq!(params.active_status, ActiveStatus::Active);
+        assert_eq!(params.media_type, MediaType::Video);
+        assert_eq!(params.search_type, SearchType::KeywordUnordered);
+    }
+
+    #[test]
+    fn search_page_request_roundtrip() {
+        use scraper_trail::request::params::Params as _;
+
+        let params = SearchPageParams {
+            search: SearchParams {
+                active_status: ActiveStatus::Active,
+                ..SearchParams::new("chess")
+            },
+            view_all_page_id: None,
+            cursor: "AQHRexampleCursor".to_string(),
+            doc_id: "123".to_string(),
+            lsd: Some("AVabc".to_string()),
+        };
+
+        let request = params.build_request(None);
+
+        assert_eq!(request.method, reqwest::Method::POST);
+        assert_eq!(request.headers["x-fb-lsd"], "AVabc");
+        assert_eq!(
+            SearchPageParams::parse_request(&request),
+            Ok(params.clone())
+        );
+        assert_eq!(
+            SearchRequestParams::parse_request(&request),
+            Ok(SearchRequestParams::Page(params))
+        );
+    }
+
+    #[test]
+    fn page_url_roundtrip() {
+        use scraper_trail::request::params::Params as _;
+
+        let params = PageParams {
+            country: Some("DE".parse().unwrap()),
+            ..PageParams::new(102_099_916_530_784)
+        };
+        let url = params.url();
+
+        assert_eq!(PageParams::parse_url(&url), Some(params));
+        assert_eq!(SearchParams::parse_url(&url), None);
+        assert_eq!(
+            SearchRequestParams::parse_request(&params.build_request(None)),
+            Ok(SearchRequestParams::ViewAllPage(params))
+        );
+
+        let next = SearchPageParams {
+            search: params.search_params(),
+            view_all_page_id: Some(params.page_id),
+            cursor: "AQHRexampleCursor".to_string(),
+            doc_id: "123".to_string(),
+            lsd: None,
+        };
+
+        assert_eq!(
+            SearchPageParams::parse_request(&next.build_request(None)),
+            Ok(next.clone())
+        );
+        assert_eq!(next.initial_url(), url);
+    }
+
+    #[test]
+    fn ad_url_is_not_search() {
+        let url = "https://www.facebook.com/ads/library/?id=576535441426103"
+            .parse()
+            .unwrap();
+
+        assert_eq!(SearchParams::parse_url(&url), None);
+        assert_eq!(
+            Params::parse_url(&url),
+            Some(Params::new(576_535_441_426_103))
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Aggregation of the Graph API's `age_country_gender_reach_breakdown` field.
+
+use super::country::Country;
+use super::{AgeRange, CountryAgeGenderBreakdowns};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Gender {
+    Female,
+    Male,
+    Unknown,
+}
+
+impl Gender {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Female => "female",
+            Self::Male => "male",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for Gender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The reach for a single country, age range, and gender.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cell<'a> {
+    pub country: Country<'a>,
+    pub age_range: &'a AgeRange,
+    pub gender: Gender,
+    pub reach: usize,
+}
+
+/// A two-dimensional table of reach totals.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pivot<R, C> {
+    pub rows: BTreeMap<R, BTreeMap<C, usize>>,
+}
+
+impl<R: Ord, C: Ord> Pivot<R, C> {
+    #[must_use]
+    pub fn get(&self, row: &R, column: &C) -> usize {
+        self.rows
+            .get(row)
+            .and_then(|columns| columns.get(column))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn row_totals(&self) -> BTreeMap<&R, usize> {
+        self.rows
+            .iter()
+            .map(|(row, columns)| (row, columns.values().sum()))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn column_totals(&self) -> BTreeMap<&C, usize> {
+        let mut totals = BTreeMap::new();
+
+        for columns in self.rows.values() {
+            for (column, reach) in columns {
+                *totals.entry(column).or_default() += reach;
+            }
+        }
+
+        totals
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.rows.values().flat_map(BTreeMap::values).sum()
+    }
+}
+
+/// The breakdown total compared to the reported EU total reach.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReachComparison {
+    pub breakdown_total: usize,
+    pub eu_total_reach: usize,
+}
+
+impl ReachComparison {
+    /// The reported total minus the breakdown total (negative if the breakdown exceeds it).
+    #[must_use]
+    pub const fn difference(&self) -> i128 {
+        self.eu_total_reach as i128 - self.breakdown_total as i128
+    }
+
+    #[must_use]
+    pub const fn is_consistent(&self) -> bool {
+        self.breakdown_total == self.eu_total_reach
+    }
+}
+
+/// A flattened view of an ad's age, country, and gender reach breakdown.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Breakdown<'a> {
+    pub cells: Vec<Cell<'a>>,
+}
+
+impl<'a> Breakdown<'a> {
+    /// Flatten the breakdowns, skipping missing values.
+    #[must_use]
+    pub fn new(breakdowns: &'a [CountryAgeGenderBreakdowns<'a>]) -> Self {
+        let cells = breakdowns
+            .iter()
+            .flat_map(|breakdown| {
+                let country = Country::from(Cow::Borrowed(breakdown.country.as_ref()));
+
+                breakdown
+                    .age_gender_breakdowns
+                    .iter()
+                    .flat_map(move |age_gender| {
+                        let country = country.clone();
+
+                        [
+                            (Gender::Female, age_gender.female),
+                            (Gender::Male, age_gender.male),
+                            (Gender::Unknown, age_gender.unknown),
+                        ]
+                        .into_iter()
+                        .filter_map(move |(gender, reach)| {
+                            reach.map(|reach| Cell {
+                                country: country.clone(),
+                                age_range: &age_gender.age_range,
+                                gender,
+                                reach,
+                            })
+                        })
+                    })
+            })
+            .collect();
+
+        Self { cells }
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.cells.iter().map(|cell| cell.reach).sum()
+    }
+
+    #[must_use]
+    pub fn by_country(&self) -> BTreeMap<Country<'a>, usize> {
+        self.totals(|cell| cell.country.clone())
+    }
+
+    #[must_use]
+    pub fn by_age_range(&self) -> BTreeMap<&'a AgeRange, usize> {
+        self.totals(|cell| cell.age_range)
+    }
+
+    #[must_use]
+    pub fn by_gender(&self) -> BTreeMap<Gender, usize> {
+        self.totals(|cell| cell.gender)
+    }
+
+    /// Build a pivot table using the given row and column keys.
+    pub fn pivot<R: Ord, C: Ord>(
+        &self,
+        row: impl Fn(&Cell<'a>) -> R,
+        column: impl Fn(&Cell<'a>) -> C,
+    ) -> Pivot<R, C> {
+        let mut rows: BTreeMap<R, BTreeMap<C, usize>> = BTreeMap::new();
+
+        for cell in &self.cells {
+            *rows
+                .entry(row(cell))
+                .or_default()
+                .entry(column(cell))
+                .or_default() += cell.reach;
+        }
+
+        Pivot { rows }
+    }
+
+    #[must_use]
+    pub fn country_by_age_range(&self) -> Pivot<Country<'a>, &'a AgeRange> {
+        self.pivot(|cell| cell.country.clone(), |cell| cell.age_range)
+    }
+
+    #[must_use]
+    pub fn country_by_gender(&self) -> Pivot<Country<'a>, Gender> {
+        self.pivot(|cell| cell.country.clone(), |cell| cell.gender)
+    }
+
+    #[must_use]
+    pub fn age_range_by_gender(&self) -> Pivot<&'a AgeRange, Gender> {
+        self.pivot(|cell| cell.age_range, |cell| cell.gender)
+    }
+
+    /// Compare the breakdown total to a reported total (usually `eu_total_reach`).
+    #[must_use]
+    pub fn compare(&self, eu_total_reach: usize) -> ReachComparison {
+        ReachComparison {
+            breakdown_total: self.total(),
+            eu_total_reach,
+        }
+    }
+
+    fn totals<K: Ord>(&self, key: impl Fn(&Cell<'a>) -> K) -> BTreeMap<K, usize> {
+        let mut totals = BTreeMap::new();
+
+        for cell in &self.cells {
+            *totals.entry(key(cell)).or_default() += cell.reach;
+        }
+
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AgeGenderBreakdown;
+
+    fn country(code: &str) -> Country<'static> {
+        Country::Known(code.parse().unwrap())
+    }
+
+    fn example() -> Vec<CountryAgeGenderBreakdowns<'static>> {
+        vec![
+            CountryAgeGenderBreakdowns {
+                country: "DE".into(),
+                age_gender_breakdowns: vec![
+                    AgeGenderBreakdown {
+                        age_range: AgeRange::Range18_24,
+                        female: Some(10),
+                        male: Some(20),
+                        unknown: None,
+                    },
+                    AgeGenderBreakdown {
+                        age_range: AgeRange::Range25_34,
+                        female: Some(5),
+                        male: Some(5),
+                        unknown: Some(1),
+                    },
+                ],
+            },
+            CountryAgeGenderBreakdowns {
+                country: "FR".into(),
+                age_gender_breakdowns: vec![AgeGenderBreakdown {
+                    age_range: AgeRange::Range18_24,
+                    female: Some(3),
+                    male: None,
+                    unknown: None,
+                }],
+            },
+        ]
+    }
+
+    #[test]
+    fn totals() {
+        let breakdowns = example();
+        let breakdown = Breakdown::new(&breakdowns);
+
+        assert_eq!(breakdown.cells.len(), 6);
+        assert_eq!(breakdown.total(), 44);
+        assert_eq!(
+            breakdown.by_country(),
+            BTreeMap::from([(country("DE"), 41), (country("FR"), 3)])
+        );
+        assert_eq!(breakdown.by_age_range()[&AgeRange::Range18_24], 33);
+        assert_eq!(
+            breakdown.by_gender(),
+            BTreeMap::from([
+                (Gender::Female, 18),
+                (Gender::Male, 25),
+                (Gender::Unknown, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn pivots() {
+        let breakdowns = example();
+        let breakdown = Breakdown::new(&breakdowns);
+        let pivot = breakdown.country_by_age_range();
+
+        assert_eq!(pivot.get(&country("DE"), &&AgeRange::Range25_34), 11);
+        assert_eq!(pivot.get(&country("FR"), &&AgeRange::Range25_34), 0);
+        assert_eq!(pivot.row_totals()[&country("DE")], 41);
+        assert_eq!(pivot.column_totals()[&&AgeRange::Range18_24], 33);
+        assert_eq!(pivot.total(), breakdown.total());
+        assert_eq!(
+            breakdown
+                .age_range_by_gender()
+                .get(&&AgeRange::Range18_24, &Gender::Female),
+            13
+        );
+    }
+
+    #[test]
+    fn compare_to_eu_total_reach() {
+        let breakdowns = example();
+        let comparison = Breakdown::new(&breakdowns).compare(40);
+
+        assert_eq!(comparison.difference(), -4);
+        assert!(!comparison.is_consistent());
+    }
+}
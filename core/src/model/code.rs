@@ -0,0 +1,143 @@
+//! Validated codes from a fixed table (e.g. ISO country and language codes).
+
+/// Define a validated code type backed by a sorted table, along with a wrapper enum that keeps
+/// values that aren't in the table.
+///
+/// Lookups are case-insensitive, and codes are normalized with the given `u8` method (e.g.
+/// `to_ascii_uppercase`). The wrapper's `Deserialize` instance is left to the caller, since some
+/// models borrow from their input and others don't.
+macro_rules! code_table {
+    (
+        $(#[$code_meta:meta])*
+        pub struct $code:ident;
+        $(#[$value_meta:meta])*
+        pub enum $value:ident;
+        codes: $codes:ident,
+        normalize: $normalize:ident,
+        error: $error:ident::$variant:ident $(,)?
+    ) => {
+        $(#[$code_meta])*
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub struct $code(&'static str);
+
+        impl $code {
+            #[must_use]
+            pub const fn as_str(self) -> &'static str {
+                self.0
+            }
+
+            pub fn all() -> impl Iterator<Item = Self> {
+                $codes.iter().map(|code| Self(code))
+            }
+        }
+
+        impl std::fmt::Display for $code {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        impl std::str::FromStr for $code {
+            type Err = $error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $codes
+                    .binary_search_by(|code| {
+                        code.bytes().cmp(s.bytes().map(|byte| byte.$normalize()))
+                    })
+                    .map(|index| Self($codes[index]))
+                    .map_err(|_| $error::$variant(s.to_string()))
+            }
+        }
+
+        impl bounded_static::ToBoundedStatic for $code {
+            type Static = Self;
+
+            fn to_static(&self) -> Self::Static {
+                *self
+            }
+        }
+
+        impl bounded_static::IntoBoundedStatic for $code {
+            type Static = Self;
+
+            fn into_static(self) -> Self::Static {
+                self
+            }
+        }
+
+        impl serde::Serialize for $code {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.0)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $code {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                serde_field_attributes::represented_as_str::deserialize(deserializer)
+            }
+        }
+
+        $(#[$value_meta])*
+        #[derive(
+            Clone,
+            Debug,
+            Eq,
+            Hash,
+            Ord,
+            PartialEq,
+            PartialOrd,
+            bounded_static_derive_more::ToStatic,
+        )]
+        pub enum $value<'a> {
+            Known($code),
+            Unknown(std::borrow::Cow<'a, str>),
+        }
+
+        impl $value<'_> {
+            #[must_use]
+            pub const fn code(&self) -> Option<$code> {
+                match self {
+                    Self::Known(code) => Some(*code),
+                    Self::Unknown(_) => None,
+                }
+            }
+
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    Self::Known(code) => code.as_str(),
+                    Self::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<std::borrow::Cow<'a, str>> for $value<'a> {
+            fn from(value: std::borrow::Cow<'a, str>) -> Self {
+                value
+                    .parse()
+                    .map_or_else(|_| Self::Unknown(value), Self::Known)
+            }
+        }
+
+        impl From<$code> for $value<'_> {
+            fn from(value: $code) -> Self {
+                Self::Known(value)
+            }
+        }
+
+        impl std::fmt::Display for $value<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $value<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+    };
+}
+
+pub(crate) use code_table;
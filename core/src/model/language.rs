@@ -0,0 +1,80 @@
+use super::code::code_table;
+use std::borrow::Cow;
+
+/// ISO 639-1 codes.
+///
+/// Must be kept sorted, since lookups use binary search.
+const CODES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+    "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+    "de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+    "ia", "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj",
+    "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb",
+    "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi",
+    "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti",
+    "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo",
+    "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid language code")]
+    InvalidLanguageCode(String),
+}
+
+code_table! {
+    /// A validated ISO 639-1 language code (always lower-case).
+    pub struct LanguageCode;
+    /// A language as reported by Meta, which will be an ISO code in almost all cases.
+    pub enum Language;
+    codes: CODES,
+    normalize: to_ascii_lowercase,
+    error: Error::InvalidLanguageCode,
+}
+
+/// Graph API ads are deserialized from owned JSON values, so languages are never borrowed.
+impl<'de> serde::Deserialize<'de> for Language<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer)
+            .map(|value| Self::from(Cow::Owned(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_sorted() {
+        assert!(CODES.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn parse_upper_case() {
+        assert_eq!("DE".parse::<LanguageCode>().unwrap().as_str(), "de");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("zz".parse::<LanguageCode>().is_err());
+        assert!("deu".parse::<LanguageCode>().is_err());
+    }
+
+    #[test]
+    fn deserialize_unknown_fallback() {
+        let languages: Vec<Language<'_>> = serde_json::from_str(r#"["de", "zh-Hans"]"#).unwrap();
+
+        assert_eq!(languages[0].code(), Some("de".parse().unwrap()));
+        assert_eq!(languages[1], Language::Unknown("zh-Hans".into()));
+    }
+
+    #[test]
+    fn serialize_normalizes_case() {
+        let language = Language::from(Cow::Borrowed("FR"));
+
+        assert_eq!(serde_json::to_string(&language).unwrap(), r#""fr""#);
+    }
+}
@@ -0,0 +1,403 @@
+//! Grouping of near-identical library ads by their collation ID.
+
+use super::media::{MediaAssets, MediaRole};
+use super::v1;
+use super::v2::{Ad, SearchResults};
+use super::versioned::AdSummary;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum GroupKey {
+    Collation(String),
+    /// Ads without a collation ID form their own group.
+    Ad(u64),
+}
+
+/// A set of ads that the library collates together.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollationGroup<'a> {
+    pub collation_id: Option<Cow<'a, str>>,
+    /// The first ad seen for the group.
+    pub representative: Ad<'a>,
+    /// Every other ad in the group, in the order seen.
+    pub members: Vec<Ad<'a>>,
+}
+
+impl<'a> CollationGroup<'a> {
+    fn new(representative: Ad<'a>) -> Self {
+        Self {
+            collation_id: representative.collation_id.clone(),
+            representative,
+            members: vec![],
+        }
+    }
+
+    /// The number of ads in the group (including the representative).
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.members.len() + 1
+    }
+
+    /// Always false, since a group has at least a representative.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The group size reported by the library, which may exceed the number of ads seen.
+    #[must_use]
+    pub fn reported_count(&self) -> Option<u64> {
+        std::iter::once(&self.representative)
+            .chain(&self.members)
+            .filter_map(|ad| ad.collation_count)
+            .max()
+    }
+
+    pub fn ads(&self) -> impl Iterator<Item = &Ad<'a>> {
+        std::iter::once(&self.representative).chain(&self.members)
+    }
+
+    /// Creative fields where a member differs from the representative.
+    #[must_use]
+    pub fn differences(&self) -> Vec<CreativeDifference<'_>> {
+        let representative = CreativeFields::new(&self.representative);
+
+        self.members
+            .iter()
+            .flat_map(|member| {
+                let fields = CreativeFields::new(member);
+
+                representative
+                    .values
+                    .iter()
+                    .zip(fields.values)
+                    .filter(|((_, left), (_, right))| left != right)
+                    .map(|((field, left), (_, right))| CreativeDifference {
+                        ad_archive_id: member.ad_archive_id,
+                        field: *field,
+                        representative: left.clone(),
+                        member: right,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// A collation group, or an ad in the older page format (which has no collation information, so is
+/// its own group).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Collated<'a> {
+    Group(Box<CollationGroup<'a>>),
+    Legacy(Box<v1::Ad<'a>>),
+}
+
+impl<'a> Collated<'a> {
+    /// The first ad seen for the group.
+    #[must_use]
+    pub fn representative(&self) -> AdSummary<'_> {
+        match self {
+            Self::Group(group) => AdSummary::from_v2(&group.representative),
+            Self::Legacy(ad) => AdSummary::from_v1(ad),
+        }
+    }
+
+    #[must_use]
+    pub fn into_group(self) -> Option<CollationGroup<'a>> {
+        match self {
+            Self::Group(group) => Some(*group),
+            Self::Legacy(_) => None,
+        }
+    }
+}
+
+/// A creative field compared between the members of a group.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CreativeField {
+    Body,
+    Title,
+    Caption,
+    LinkDescription,
+    LinkUrl,
+    CtaText,
+    DisplayFormat,
+    /// The URLs of the creative's images and videos (excluding profile pictures).
+    Media,
+}
+
+impl CreativeField {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Body => "body",
+            Self::Title => "title",
+            Self::Caption => "caption",
+            Self::LinkDescription => "link_description",
+            Self::LinkUrl => "link_url",
+            Self::CtaText => "cta_text",
+            Self::DisplayFormat => "display_format",
+            Self::Media => "media",
+        }
+    }
+}
+
+impl Display for CreativeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A difference between a group member and the group's representative.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreativeDifference<'a> {
+    /// The member's ad ID.
+    pub ad_archive_id: u64,
+    pub field: CreativeField,
+    pub representative: Option<Cow<'a, str>>,
+    pub member: Option<Cow<'a, str>>,
+}
+
+struct CreativeFields<'a> {
+    values: [(CreativeField, Option<Cow<'a, str>>); 8],
+}
+
+impl<'a> CreativeFields<'a> {
+    fn new(ad: &'a Ad<'_>) -> Self {
+        let snapshot = &ad.snapshot;
+        let media = ad
+            .media_assets()
+            .into_iter()
+            .filter(|asset| {
+                !matches!(
+                    asset.role,
+                    MediaRole::PageProfilePicture | MediaRole::InstagramProfilePicture
+                )
+            })
+            .map(|asset| asset.url)
+            .collect::<Vec<_>>();
+
+        Self {
+            values: [
+                (
+                    CreativeField::Body,
+                    snapshot
+                        .body
+                        .as_ref()
+                        .map(|body| Cow::from(body.text.as_ref())),
+                ),
+                (
+                    CreativeField::Title,
+                    snapshot.title.as_deref().map(Cow::from),
+                ),
+                (
+                    CreativeField::Caption,
+                    snapshot.caption.as_deref().map(Cow::from),
+                ),
+                (
+                    CreativeField::LinkDescription,
+                    snapshot.link_description.as_deref().map(Cow::from),
+                ),
+                (
+                    CreativeField::LinkUrl,
+                    snapshot.link_url.as_deref().map(Cow::from),
+                ),
+                (
+                    CreativeField::CtaText,
+                    snapshot.cta_text.as_deref().map(Cow::from),
+                ),
+                (
+                    CreativeField::DisplayFormat,
+                    snapshot
+                        .display_format
+                        .as_ref()
+                        .map(|display_format| Cow::from(display_format.as_str())),
+                ),
+                (
+                    CreativeField::Media,
+                    (!media.is_empty()).then(|| Cow::from(media.join(" "))),
+                ),
+            ],
+        }
+    }
+}
+
+/// Builds collation groups incrementally (e.g. across the pages of an archive).
+///
+/// An ad ID that has already been seen is ignored, so the first version of an ad wins.
+#[derive(Clone, Debug, Default)]
+pub struct Collator<'a> {
+    groups: Vec<Collated<'a>>,
+    indices: HashMap<GroupKey, usize>,
+    seen: HashSet<u64>,
+}
+
+impl<'a> Collator<'a> {
+    pub fn push(&mut self, ad: Ad<'a>) {
+        if self.seen.insert(ad.ad_archive_id) {
+            let key = ad
+                .collation_id
+                .as_ref()
+                .map_or(GroupKey::Ad(ad.ad_archive_id), |collation_id| {
+                    GroupKey::Collation(collation_id.to_string())
+                });
+
+            let next = self.groups.len();
+            let index = *self.indices.entry(key).or_insert(next);
+
+            if index == next {
+                self.groups
+                    .push(Collated::Group(Box::new(CollationGroup::new(ad))));
+            } else if let Collated::Group(group) = &mut self.groups[index] {
+                group.members.push(ad);
+            }
+        }
+    }
+
+    /// Add an ad in the older page format as a group of its own.
+    pub fn push_v1(&mut self, ad: Box<v1::Ad<'a>>) {
+        if self.seen.insert(ad.deeplink_ad_card.ad_archive_id) {
+            self.groups.push(Collated::Legacy(ad));
+        }
+    }
+
+    /// The groups in the order their first ad was seen.
+    #[must_use]
+    pub fn finish(self) -> Vec<Collated<'a>> {
+        self.groups
+    }
+}
+
+impl<'a> Extend<Ad<'a>> for Collator<'a> {
+    fn extend<I: IntoIterator<Item = Ad<'a>>>(&mut self, iter: I) {
+        for ad in iter {
+            self.push(ad);
+        }
+    }
+}
+
+/// Group ads by collation ID, preserving the order in which groups are first seen.
+pub fn group<'a, I: IntoIterator<Item = Ad<'a>>>(ads: I) -> Vec<CollationGroup<'a>> {
+    let mut collator = Collator::default();
+    collator.extend(ads);
+    collator
+        .finish()
+        .into_iter()
+        .filter_map(Collated::into_group)
+        .collect()
+}
+
+impl<'a> SearchResults<'a> {
+    #[must_use]
+    pub fn into_collation_groups(self) -> Vec<CollationGroup<'a>> {
+        group(self.ads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::library::v2::AdLibraryResult;
+    use serde_json::Value;
+
+    const SEARCH_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-search-01.json");
+
+    #[test]
+    fn group_example_search_01() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(SEARCH_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let groups = result.into_search_results().into_collation_groups();
+
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (group.collation_id.as_deref(), group.len()))
+                .collect::<Vec<_>>(),
+            vec![(Some("1234567890"), 3), (None, 1), (Some("9876543210"), 1)]
+        );
+        assert_eq!(groups[0].representative.ad_archive_id, 576_535_441_426_103);
+        assert_eq!(groups[0].reported_count(), Some(3));
+
+        let differences = groups[0].differences();
+
+        assert_eq!(
+            differences
+                .iter()
+                .map(|difference| (difference.ad_archive_id, difference.field))
+                .collect::<Vec<_>>(),
+            vec![
+                (576_535_441_426_104, CreativeField::Body),
+                (576_535_441_426_105, CreativeField::DisplayFormat),
+                (576_535_441_426_105, CreativeField::Media)
+            ]
+        );
+        assert_eq!(
+            differences[0].member.as_deref(),
+            Some("Play chess against human-like bots today.")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collator_ignores_repeated_ads() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(SEARCH_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let ads = result.into_search_results().ads;
+
+        let v1_value = serde_json::json!([{
+            "markup": [["u_0_1", {"__html": "<div>Play chess</div>"}, 1, "HTML"]],
+            "deeplinkAdCard": {
+                "adArchiveID": "123456789",
+                "snapshot": {
+                    "creation_time": 1_700_000_000,
+                    "page_id": "157817344084965",
+                    "page_name": "Chessiverse",
+                    "page_profile_picture_url": "https://scontent.xx.fbcdn.net/profile.jpg",
+                    "page_entity_type": "regular_page",
+                    "page_is_profile_page": false,
+                    "page_like_count": 100,
+                    "instagram_url": "",
+                    "instagram_handle": "",
+                    "instagram_actor_name": "",
+                    "instagram_profile_pic_url": "",
+                    "videos": []
+                }
+            }
+        }]);
+        let v1_ad = v1::Ad::extract(&v1_value)?.expect("v1 ad");
+
+        let mut collator = Collator::default();
+        collator.push_v1(Box::new(v1_ad.clone()));
+        collator.extend(ads.clone());
+        collator.push_v1(Box::new(v1_ad));
+        collator.extend(ads);
+
+        let collated = collator.finish();
+
+        assert_eq!(
+            collated
+                .iter()
+                .map(|collated| collated.representative().ad_id)
+                .collect::<Vec<_>>(),
+            vec![
+                123_456_789,
+                576_535_441_426_103,
+                576_535_441_426_106,
+                576_535_441_426_107
+            ]
+        );
+        assert!(matches!(collated[0], Collated::Legacy(_)));
+
+        let groups = collated
+            .into_iter()
+            .filter_map(Collated::into_group)
+            .collect::<Vec<_>>();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].len(), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+//! An inventory of the media assets referenced by library ads.
+
+use super::{Video, v1, v2};
+use std::borrow::Cow;
+use std::fmt::Display;
+
+/// A single media asset URL, with a description of what it is.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+pub struct MediaAsset<'a> {
+    pub kind: MediaKind,
+    pub role: MediaRole,
+    pub resolution: Resolution,
+    pub watermarked: bool,
+    pub url: Cow<'a, str>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Image,
+    Video,
+    /// A still image shown before a video plays.
+    VideoPreview,
+}
+
+impl MediaKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+            Self::VideoPreview => "video_preview",
+        }
+    }
+}
+
+impl Display for MediaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where in the ad the asset appears.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaRole {
+    PageProfilePicture,
+    InstagramProfilePicture,
+    /// The main creative of the ad.
+    Creative,
+    /// A carousel or product card (with its zero-based position).
+    Card(usize),
+    /// A dynamic creative variant.
+    Variant,
+}
+
+impl Display for MediaRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PageProfilePicture => f.write_str("page_profile_picture"),
+            Self::InstagramProfilePicture => f.write_str("instagram_profile_picture"),
+            Self::Creative => f.write_str("creative"),
+            Self::Card(index) => write!(f, "card_{index}"),
+            Self::Variant => f.write_str("variant"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    Original,
+    Resized,
+    Hd,
+    Sd,
+    /// The only version available.
+    Default,
+}
+
+impl Resolution {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Original => "original",
+            Self::Resized => "resized",
+            Self::Hd => "hd",
+            Self::Sd => "sd",
+            Self::Default => "default",
+        }
+    }
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Models that reference media assets.
+pub trait MediaAssets {
+    /// Every media asset, in document order, with duplicate URLs removed.
+    fn media_assets(&self) -> Vec<MediaAsset<'_>>;
+}
+
+#[derive(Default)]
+struct Inventory<'a> {
+    assets: Vec<MediaAsset<'a>>,
+}
+
+impl<'a> Inventory<'a> {
+    fn push<U: Into<Cow<'a, str>>>(
+        &mut self,
+        kind: MediaKind,
+        role: MediaRole,
+        resolution: Resolution,
+        watermarked: bool,
+        url: Option<U>,
+    ) {
+        if let Some(url) = url {
+            let url = url.into();
+
+            if !url.is_empty() && !self.assets.iter().any(|asset| asset.url == url) {
+                self.assets.push(MediaAsset {
+                    kind,
+                    role,
+                    resolution,
+                    watermarked,
+                    url,
+                });
+            }
+        }
+    }
+
+    fn push_video(&mut self, role: MediaRole, video: &'a Video<'a>) {
+        self.push_video_fields(
+            role,
+            [video.video_hd_url.as_deref(), video.video_sd_url.as_deref()],
+            [
+                video.watermarked_video_hd_url.as_deref(),
+                video.watermarked_video_sd_url.as_deref(),
+            ],
+            video.video_preview_image_url.as_deref(),
+        );
+    }
+
+    /// The URL pairs are in HD, SD order.
+    fn push_video_fields(
+        &mut self,
+        role: MediaRole,
+        [hd, sd]: [Option<&'a str>; 2],
+        watermarked: [Option<&'a str>; 2],
+        preview: Option<&'a str>,
+    ) {
+        self.push(MediaKind::Video, role, Resolution::Hd, false, hd);
+        self.push(MediaKind::Video, role, Resolution::Sd, false, sd);
+        self.push(MediaKind::Video, role, Resolution::Hd, true, watermarked[0]);
+        self.push(MediaKind::Video, role, Resolution::Sd, true, watermarked[1]);
+        self.push(
+            MediaKind::VideoPreview,
+            role,
+            Resolution::Default,
+            false,
+            preview,
+        );
+    }
+
+    fn push_image(&mut self, role: MediaRole, image: &'a v2::Image<'a>) {
+        self.push_image_fields(
+            role,
+            Some(&image.original_image_url),
+            Some(&image.resized_image_url),
+            Some(&image.watermarked_resized_image_url),
+        );
+    }
+
+    fn push_image_fields(
+        &mut self,
+        role: MediaRole,
+        original: Option<&'a str>,
+        resized: Option<&'a str>,
+        watermarked_resized: Option<&'a str>,
+    ) {
+        self.push(
+            MediaKind::Image,
+            role,
+            Resolution::Original,
+            false,
+            original,
+        );
+        self.push(MediaKind::Image, role, Resolution::Resized, false, resized);
+        self.push(
+            MediaKind::Image,
+            role,
+            Resolution::Resized,
+            true,
+            watermarked_resized,
+        );
+    }
+}
+
+impl MediaAssets for v1::Snapshot<'_> {
+    fn media_assets(&self) -> Vec<MediaAsset<'_>> {
+        let mut inventory = Inventory::default();
+
+        inventory.push(
+            MediaKind::Image,
+            MediaRole::PageProfilePicture,
+            Resolution::Default,
+            false,
+            Some(self.page_profile_picture_url.as_ref()),
+        );
+        inventory.push(
+            MediaKind::Image,
+            MediaRole::InstagramProfilePicture,
+            Resolution::Default,
+            false,
+            Some(self.instagram_profile_pic_url.as_ref()),
+        );
+
+        for video in &self.videos {
+            inventory.push_video(MediaRole::Creative, video);
+        }
+
+        inventory.assets
+    }
+}
+
+impl MediaAssets for v1::Ad<'_> {
+    /// Includes the images and videos found in the rendered markup.
+    fn media_assets(&self) -> Vec<MediaAsset<'_>> {
+        let mut inventory = Inventory {
+            assets: self.deeplink_ad_card.snapshot.media_assets(),
+        };
+
+        let creative = self.creative();
+
+        for url in creative.image_urls {
+            inventory.push(
+                MediaKind::Image,
+                MediaRole::Creative,
+                Resolution::Default,
+                false,
+                Some(url),
+            );
+        }
+
+        for url in creative.video_urls {
+            inventory.push(
+                MediaKind::Video,
+                MediaRole::Creative,
+                Resolution::Default,
+                false,
+                Some(url),
+            );
+        }
+
+        inventory.assets
+    }
+}
+
+impl MediaAssets for v2::Snapshot<'_> {
+    fn media_assets(&self) -> Vec<MediaAsset<'_>> {
+        let mut inventory = Inventory::default();
+
+        inventory.push(
+            MediaKind::Image,
+            MediaRole::PageProfilePicture,
+            Resolution::Default,
+            false,
+            Some(self.page_profile_picture_url.as_ref()),
+        );
+
+        for image in &self.images {
+            inventory.push_image(MediaRole::Creative, image);
+        }
+
+        for video in &self.videos {
+            inventory.push_video(MediaRole::Creative, video);
+        }
+
+        for (index, card) in self.cards.iter().enumerate() {
+            let role = MediaRole::Card(index);
+
+            inventory.push_image_fields(
+                role,
+                card.original_image_url.as_deref(),
+                card.resized_image_url.as_deref(),
+                card.watermarked_resized_image_url.as_deref(),
+            );
+            inventory.push_video_fields(
+                role,
+                [card.video_hd_url.as_deref(), card.video_sd_url.as_deref()],
+                [
+                    card.watermarked_video_hd_url.as_deref(),
+                    card.watermarked_video_sd_url.as_deref(),
+                ],
+                card.video_preview_image_url.as_deref(),
+            );
+        }
+
+        for image in self.extra_images.iter().filter_map(v2::ExtraImage::image) {
+            inventory.push_image(MediaRole::Variant, image);
+        }
+
+        for video in &self.extra_videos {
+            inventory.push_video(MediaRole::Variant, video);
+        }
+
+        inventory.assets
+    }
+}
+
+impl MediaAssets for v2::Ad<'_> {
+    fn media_assets(&self) -> Vec<MediaAsset<'_>> {
+        self.snapshot.media_assets()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const SEARCH_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-search-01.json");
+
+    #[test]
+    fn inventory_example_search_01() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(SEARCH_01_EXAMPLE)?;
+        let result = v2::AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let ads = result.into_search_results().ads;
+
+        // The profile picture and the three image sizes.
+        assert_eq!(ads[0].media_assets().len(), 4);
+
+        let assets = ads[2].media_assets();
+        let variants = assets
+            .iter()
+            .filter(|asset| asset.role == MediaRole::Variant)
+            .collect::<Vec<_>>();
+
+        // Three image sizes, two video sizes, and a preview.
+        assert_eq!(variants.len(), 6);
+        assert!(
+            variants
+                .iter()
+                .any(|asset| asset.kind == MediaKind::VideoPreview
+                    && asset.url == "https://scontent.xx.fbcdn.net/variant-preview.jpg")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn inventory_deduplicates_urls() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(SEARCH_01_EXAMPLE)?;
+        let result = v2::AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let mut ad = result.into_search_results().ads.remove(0);
+
+        ad.snapshot.images.push(ad.snapshot.images[0].clone());
+
+        assert_eq!(ad.media_assets().len(), 4);
+
+        Ok(())
+    }
+}
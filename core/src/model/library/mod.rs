@@ -1,16 +1,144 @@
+pub mod collation;
+pub mod media;
+pub mod template;
 pub mod v1;
 pub mod v2;
+pub mod versioned;
 
+use bounded_static_derive_more::ToStatic;
+use serde_json::Value;
 use std::borrow::Cow;
 
 /// A video asset, shared between the library models.
 ///
 /// All URL fields are optional because availability varies by ad format and version.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Video<'a> {
+    #[serde(borrow, default, with = "optional_cow_str")]
     pub video_hd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "optional_cow_str")]
     pub video_sd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "optional_cow_str")]
     pub watermarked_video_hd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "optional_cow_str")]
     pub watermarked_video_sd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "optional_cow_str")]
     pub video_preview_image_url: Option<Cow<'a, str>>,
 }
+
+/// A JSON value in a shape the models don't recognize yet.
+///
+/// This wrapper only exists so that fallback variants can be used in enums deriving `ToStatic`.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct Unparsed(pub serde_json::Value);
+
+impl bounded_static::ToBoundedStatic for Unparsed {
+    type Static = Self;
+
+    fn to_static(&self) -> Self::Static {
+        self.clone()
+    }
+}
+
+impl bounded_static::IntoBoundedStatic for Unparsed {
+    type Static = Self;
+
+    fn into_static(self) -> Self::Static {
+        self
+    }
+}
+
+/// JSON scripts that library models can be extracted from.
+///
+/// Models extracted from a shared reference borrow their strings from it, while models extracted
+/// from a mutable reference take their strings out of the value, so that owned models (e.g. from
+/// archived pages) can be parsed without copying every string twice.
+pub trait JsonSource<'de>: Sized {
+    type Key: AsRef<str>;
+    type Items: Iterator<Item = Self>;
+    type Entries: Iterator<Item = (Self::Key, Self)>;
+
+    fn is_empty_array(&self) -> bool;
+
+    fn children(self) -> Children<Self::Items, Self::Entries>;
+
+    fn parse<T: serde::Deserialize<'de>>(self) -> Result<T, serde_json::Error>;
+}
+
+/// The items of an array or the entries of an object.
+pub enum Children<I, E> {
+    Array(I),
+    Object(E),
+    None,
+}
+
+impl<'a> JsonSource<'a> for &'a Value {
+    type Key = &'a String;
+    type Items = std::slice::Iter<'a, Value>;
+    type Entries = serde_json::map::Iter<'a>;
+
+    fn is_empty_array(&self) -> bool {
+        self.as_array().is_some_and(Vec::is_empty)
+    }
+
+    fn children(self) -> Children<Self::Items, Self::Entries> {
+        match self {
+            Value::Array(items) => Children::Array(items.iter()),
+            Value::Object(entries) => Children::Object(entries.iter()),
+            _ => Children::None,
+        }
+    }
+
+    fn parse<T: serde::Deserialize<'a>>(self) -> Result<T, serde_json::Error> {
+        T::deserialize(self)
+    }
+}
+
+impl<'v> JsonSource<'static> for &'v mut Value {
+    type Key = &'v String;
+    type Items = std::slice::IterMut<'v, Value>;
+    type Entries = serde_json::map::IterMut<'v>;
+
+    fn is_empty_array(&self) -> bool {
+        self.as_array().is_some_and(Vec::is_empty)
+    }
+
+    fn children(self) -> Children<Self::Items, Self::Entries> {
+        match self {
+            Value::Array(items) => Children::Array(items.iter_mut()),
+            Value::Object(entries) => Children::Object(entries.iter_mut()),
+            _ => Children::None,
+        }
+    }
+
+    /// Parse the value, leaving `null` in its place.
+    fn parse<T: serde::Deserialize<'static>>(self) -> Result<T, serde_json::Error> {
+        T::deserialize(self.take())
+    }
+}
+
+/// Borrowing (de)serialization for optional strings.
+///
+/// Serde's `#[serde(borrow)]` only borrows fields whose type is exactly `Cow<'a, str>`, so without
+/// this every optional string would be copied even when the input could be borrowed.
+pub(crate) mod optional_cow_str {
+    use crate::model::BorrowedStr;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+
+    pub fn deserialize<'de: 'a, 'a, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Cow<'a, str>>, D::Error> {
+        Option::<BorrowedStr<'a>>::deserialize(deserializer)
+            .map(|value| value.map(|BorrowedStr(value)| value))
+    }
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S: Serializer>(
+        value: &Option<Cow<'_, str>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+}
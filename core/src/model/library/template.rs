@@ -0,0 +1,246 @@
+//! Placeholder templates in dynamic product and dynamic creative ads.
+//!
+//! Catalog-driven ads often carry copy like `Shop {{product.brand}} now`, with the actual values
+//! only available (if at all) on individual cards.
+
+use super::v2::{Card, Snapshot};
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+static PLACEHOLDER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([^{}]*?)\s*\}\}").unwrap());
+
+/// A catalog field referenced by a placeholder.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ProductField {
+    Name,
+    Brand,
+    Description,
+    Price,
+    Url,
+    Unknown,
+}
+
+impl ProductField {
+    fn from_key(key: &str) -> Self {
+        let key = key.to_ascii_lowercase();
+
+        match key.strip_prefix("product.").unwrap_or(&key) {
+            "name" | "title" => Self::Name,
+            "brand" => Self::Brand,
+            "description" => Self::Description,
+            "price" | "current_price" | "sale_price" => Self::Price,
+            "url" | "link" => Self::Url,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The card value for this field, if the card has a literal one.
+    fn resolve<'c>(self, card: &'c Card<'_>) -> Option<&'c str> {
+        let literal = |value: &'c Option<Cow<'_, str>>| {
+            value
+                .as_deref()
+                .filter(|value| !value.is_empty() && !has_placeholders(value))
+        };
+
+        match self {
+            Self::Name => literal(&card.title),
+            Self::Description => literal(&card.link_description).or_else(|| literal(&card.body)),
+            Self::Url => literal(&card.link_url),
+            // Cards don't carry brand or price information.
+            Self::Brand | Self::Price | Self::Unknown => None,
+        }
+    }
+}
+
+/// A placeholder occurring in a template.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Placeholder<'a> {
+    /// The full placeholder text, including braces.
+    pub raw: &'a str,
+    /// The trimmed key (e.g. `product.name`).
+    pub key: &'a str,
+    pub field: ProductField,
+}
+
+/// Whether the text contains at least one `{{...}}` placeholder.
+#[must_use]
+pub fn has_placeholders(text: &str) -> bool {
+    PLACEHOLDER_RE.is_match(text)
+}
+
+/// All placeholders in the text, in order of appearance.
+#[must_use]
+pub fn placeholders(text: &str) -> Vec<Placeholder<'_>> {
+    PLACEHOLDER_RE
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let raw = captures.get(0)?.as_str();
+            let key = captures.get(1)?.as_str();
+
+            Some(Placeholder {
+                raw,
+                key,
+                field: ProductField::from_key(key),
+            })
+        })
+        .collect()
+}
+
+/// The copy of a single card, with placeholders substituted where possible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConcreteCreative<'a> {
+    /// The zero-based position of the card.
+    pub card_index: usize,
+    pub body: Option<Cow<'a, str>>,
+    pub title: Option<Cow<'a, str>>,
+    pub caption: Option<Cow<'a, str>>,
+    pub link_description: Option<Cow<'a, str>>,
+    pub link_url: Option<Cow<'a, str>>,
+    pub cta_text: Option<Cow<'a, str>>,
+    /// Placeholder keys that could not be resolved (left verbatim in the text), de-duplicated.
+    pub unresolved: Vec<String>,
+}
+
+impl ConcreteCreative<'_> {
+    #[must_use]
+    pub const fn is_resolved(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+}
+
+/// One concrete creative per card of the snapshot.
+///
+/// Card fields take precedence over snapshot fields, and placeholders in either are substituted
+/// with the card's literal values. Snapshots without cards produce no creatives.
+#[must_use]
+pub fn expand<'a>(snapshot: &'a Snapshot<'a>) -> Vec<ConcreteCreative<'a>> {
+    snapshot
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(card_index, card)| {
+            let mut unresolved = vec![];
+
+            let mut fill = |card_value: &'a Option<Cow<'a, str>>,
+                            snapshot_value: Option<&'a str>| {
+                card_value
+                    .as_deref()
+                    .or(snapshot_value)
+                    .map(|template| substitute(template, card, &mut unresolved))
+            };
+
+            let body = fill(
+                &card.body,
+                snapshot.body.as_ref().map(|body| body.text.as_ref()),
+            );
+            let title = fill(&card.title, snapshot.title.as_deref());
+            let caption = fill(&card.caption, snapshot.caption.as_deref());
+            let link_description =
+                fill(&card.link_description, snapshot.link_description.as_deref());
+            let link_url = fill(&card.link_url, snapshot.link_url.as_deref());
+            let cta_text = fill(&card.cta_text, snapshot.cta_text.as_deref());
+
+            ConcreteCreative {
+                card_index,
+                body,
+                title,
+                caption,
+                link_description,
+                link_url,
+                cta_text,
+                unresolved,
+            }
+        })
+        .collect()
+}
+
+fn substitute<'a>(
+    template: &'a str,
+    card: &Card<'_>,
+    unresolved: &mut Vec<String>,
+) -> Cow<'a, str> {
+    PLACEHOLDER_RE.replace_all(template, |captures: &Captures<'_>| {
+        let key = &captures[1];
+
+        ProductField::from_key(key).resolve(card).map_or_else(
+            || {
+                if !unresolved.iter().any(|value| value == key) {
+                    unresolved.push(key.to_string());
+                }
+
+                captures[0].to_string()
+            },
+            str::to_string,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::library::v2::{AdLibraryResult, DisplayFormat};
+    use serde_json::Value;
+
+    const AD_DPA_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-ad-dpa-01.json");
+
+    #[test]
+    fn parse_placeholders() {
+        let found = placeholders("Shop {{ product.brand }} now: {{product.name}} {{foo}}");
+
+        assert_eq!(
+            found
+                .iter()
+                .map(|placeholder| (placeholder.key, placeholder.field))
+                .collect::<Vec<_>>(),
+            vec![
+                ("product.brand", ProductField::Brand),
+                ("product.name", ProductField::Name),
+                ("foo", ProductField::Unknown)
+            ]
+        );
+        assert_eq!(found[0].raw, "{{ product.brand }}");
+        assert!(!has_placeholders("No templates {here}"));
+    }
+
+    #[test]
+    fn expand_example_ad_dpa_01() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(AD_DPA_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let ad = result.ad().expect("deeplink ad");
+
+        assert_eq!(ad.snapshot.display_format, Some(DisplayFormat::Dpa));
+        assert!(ad.snapshot.is_templated());
+
+        let creatives = ad.snapshot.expand_templates();
+
+        assert_eq!(creatives.len(), 3);
+        assert_eq!(creatives[0].title.as_deref(), Some("Wooden Chess Set"));
+        assert_eq!(
+            creatives[0].body.as_deref(),
+            Some("Shop {{product.brand}} now: Wooden Chess Set")
+        );
+        assert_eq!(creatives[0].unresolved, vec!["product.brand"]);
+
+        // The second card has no description, so the snapshot template is used and left as is.
+        assert_eq!(
+            creatives[1].link_description.as_deref(),
+            Some("{{product.description}}")
+        );
+        assert_eq!(
+            creatives[1].unresolved,
+            vec!["product.brand", "product.description"]
+        );
+
+        // The third card is itself templated.
+        assert_eq!(creatives[2].title.as_deref(), Some("{{product.name}}"));
+        assert_eq!(
+            creatives[2].unresolved,
+            vec!["product.brand", "product.name", "product.price"]
+        );
+        assert!(!creatives[2].is_resolved());
+
+        Ok(())
+    }
+}
@@ -1,8 +1,28 @@
+use super::{Children, JsonSource};
+use bounded_static_derive_more::ToStatic;
 use chrono::{DateTime, Utc};
+use scraper::{ElementRef, Html, Selector};
 use scraper_trail::archive::Archiveable;
 use serde_field_attributes::integer_or_integer_str;
-use serde_json::Value;
 use std::borrow::Cow;
+use std::sync::LazyLock;
+
+// Only Meta's `data-ad-preview` attributes are used, since the obfuscated class names change from
+// one build of the page to the next (and other buttons, like menus, can appear outside the ad).
+static BODY_SEL: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(r#"[data-ad-preview="message"]"#).unwrap());
+static HEADLINE_SEL: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(r#"[data-ad-preview="headline"]"#).unwrap());
+static CTA_SEL: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(r#"[data-ad-preview="cta"]"#).unwrap());
+static LINK_SEL: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a[href]").unwrap());
+static IMAGE_SEL: LazyLock<Selector> = LazyLock::new(|| Selector::parse("img[src]").unwrap());
+static VIDEO_SEL: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("video[src], video source[src]").unwrap());
+
+/// Relative links and media URLs in the markup are resolved against this.
+static BASE_URL: LazyLock<url::Url> =
+    LazyLock::new(|| url::Url::parse("https://www.facebook.com/").unwrap());
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -24,16 +44,23 @@ struct PartialAd<'a> {
     deeplink_ad_card_values: Vec<Option<DeeplinkAdCard<'a>>>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
 pub struct Ad<'a> {
     pub markup: Markup<'a>,
     pub deeplink_ad_card: DeeplinkAdCard<'a>,
 }
 
 impl<'a> Ad<'a> {
+    #[must_use]
+    pub fn creative(&self) -> Creative<'_> {
+        self.markup.creative()
+    }
+
+    /// Extract the ad from the page's JSON scripts (see [`JsonSource`] for how strings are
+    /// handled).
     #[allow(clippy::missing_panics_doc)]
-    pub fn extract(value: &Value) -> Result<Option<Self>, Error> {
-        if value.as_array().is_some_and(std::vec::Vec::is_empty) {
+    pub fn extract<S: JsonSource<'a>>(value: S) -> Result<Option<Self>, Error> {
+        if value.is_empty_array() {
             Ok(None)
         } else {
             let mut partial_ad = PartialAd::default();
@@ -69,35 +96,39 @@ impl<'a> Ad<'a> {
         }
     }
 
-    fn extract_rec(value: &Value, current: &mut PartialAd<'a>) -> Result<(), Error> {
-        if let Some(as_array) = value.as_array() {
-            for value in as_array {
-                Self::extract_rec(value, current)?;
-            }
-        } else if let Some(as_object) = value.as_object() {
-            for (key, value) in as_object {
-                if key == "markup" {
-                    let markup = serde_json::from_value::<MarkupElement<'a>>(value.clone())?;
-
-                    current.markup_values.push(Markup {
-                        id: markup.0.0,
-                        html: markup.0.1.html,
-                    });
-                } else if key == "deeplinkAdCard" {
-                    let snapshot = serde_json::from_value(value.clone())?;
-
-                    current.deeplink_ad_card_values.push(snapshot);
-                } else {
+    fn extract_rec<S: JsonSource<'a>>(value: S, current: &mut PartialAd<'a>) -> Result<(), Error> {
+        match value.children() {
+            Children::Array(items) => {
+                for value in items {
                     Self::extract_rec(value, current)?;
                 }
             }
+            Children::Object(entries) => {
+                for (key, value) in entries {
+                    match key.as_ref() {
+                        "markup" => {
+                            let (markup,): MarkupElement<'a> = value.parse()?;
+
+                            current.markup_values.push(Markup {
+                                id: markup.0,
+                                html: markup.1.html,
+                            });
+                        }
+                        "deeplinkAdCard" => {
+                            current.deeplink_ad_card_values.push(value.parse()?);
+                        }
+                        _ => Self::extract_rec(value, current)?,
+                    }
+                }
+            }
+            Children::None => {}
         }
 
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
 pub enum AdResponse<'a> {
     Value(Box<Ad<'a>>),
     Empty,
@@ -135,7 +166,7 @@ impl Archiveable for AdResponse<'_> {
 
         next.map(|(field, response)| {
             response
-                .and_then(|data| Ad::extract(&data).map(std::convert::Into::into))
+                .and_then(|mut data| Ad::extract(&mut data).map(AdResponse::from))
                 .map(|response| (field, response))
         })
         .map_or(Ok(None), |value| {
@@ -144,11 +175,19 @@ impl Archiveable for AdResponse<'_> {
     }
 }
 
-type MarkupElement<'a> = ((Cow<'a, str>, MarkupHtml<'a>, u8, MarkupType),);
+type MarkupElement<'a> = (MarkupEntry<'a>,);
+
+#[derive(serde::Deserialize)]
+struct MarkupEntry<'a>(
+    #[serde(borrow)] Cow<'a, str>,
+    #[serde(borrow)] MarkupHtml<'a>,
+    #[allow(dead_code)] u8,
+    #[allow(dead_code)] MarkupType,
+);
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 struct MarkupHtml<'a> {
-    #[serde(rename = "__html")]
+    #[serde(borrow, rename = "__html")]
     html: Cow<'a, str>,
 }
 
@@ -158,37 +197,173 @@ enum MarkupType {
     Html,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Markup<'a> {
+    #[serde(borrow)]
     pub id: Cow<'a, str>,
+    #[serde(borrow)]
     pub html: Cow<'a, str>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+impl Markup<'_> {
+    /// Parse the rendered ad HTML into its creative content.
+    ///
+    /// This is best-effort, since the markup is not a stable format. Values are borrowed from the
+    /// markup when they appear there verbatim (i.e. without entities or whitespace to normalize).
+    #[must_use]
+    pub fn creative(&self) -> Creative<'_> {
+        let html = Html::parse_fragment(&self.html);
+        let source = self.html.as_ref();
+
+        let first_text = |selector: &Selector| {
+            html.select(selector)
+                .map(|element| element_text(source, element))
+                .find(|text| !text.is_empty())
+        };
+
+        let urls = |selector: &Selector, internal: bool| {
+            unique(
+                html.select(selector)
+                    .filter(|element| internal || !links_internally(*element))
+                    .filter_map(|element| element.value().attr("src"))
+                    .filter_map(|src| resolve(source, src)),
+            )
+        };
+
+        Creative {
+            body: first_text(&BODY_SEL),
+            title: first_text(&HEADLINE_SEL),
+            cta_text: first_text(&CTA_SEL),
+            link_urls: unique(
+                html.select(&LINK_SEL)
+                    .filter_map(|element| element.value().attr("href"))
+                    .filter_map(|href| unwrap_link(source, href)),
+            ),
+            image_urls: urls(&IMAGE_SEL, false),
+            video_urls: urls(&VIDEO_SEL, true),
+        }
+    }
+}
+
+/// The creative content extracted from v1 markup, corresponding to the fields of a v2 snapshot.
+#[derive(Clone, Debug, Default, Eq, PartialEq, ToStatic, serde::Serialize)]
+pub struct Creative<'a> {
+    pub body: Option<Cow<'a, str>>,
+    pub title: Option<Cow<'a, str>>,
+    pub cta_text: Option<Cow<'a, str>>,
+    /// Link targets, with Facebook's redirect wrappers removed.
+    pub link_urls: Vec<Cow<'a, str>>,
+    pub image_urls: Vec<Cow<'a, str>>,
+    pub video_urls: Vec<Cow<'a, str>>,
+}
+
+/// Borrow the value from the source markup if it appears there verbatim.
+fn borrow_from<'a>(source: &'a str, value: &str) -> Cow<'a, str> {
+    source.find(value).map_or_else(
+        || Cow::Owned(value.to_string()),
+        |start| Cow::Borrowed(&source[start..start + value.len()]),
+    )
+}
+
+fn element_text<'a>(source: &'a str, element: ElementRef<'_>) -> Cow<'a, str> {
+    let text = element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    borrow_from(source, &text)
+}
+
+/// Whether the element is inside a link to a Facebook page (e.g. the advertiser's profile picture).
+fn links_internally(element: ElementRef<'_>) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .filter_map(|ancestor| ancestor.value().attr("href"))
+        .any(|href| unwrap_link("", href).is_none())
+}
+
+fn unique<'a, I: Iterator<Item = Cow<'a, str>>>(values: I) -> Vec<Cow<'a, str>> {
+    let mut result: Vec<Cow<'a, str>> = vec![];
+
+    for value in values {
+        if !result.contains(&value) {
+            result.push(value);
+        }
+    }
+
+    result
+}
+
+/// Resolve a (possibly relative) URL from the markup, borrowing it if it was already absolute.
+fn resolve<'a>(source: &'a str, href: &str) -> Option<Cow<'a, str>> {
+    if url::Url::parse(href).is_ok() {
+        Some(borrow_from(source, href))
+    } else {
+        BASE_URL
+            .join(href)
+            .ok()
+            .map(|url| Cow::Owned(url.to_string()))
+    }
+}
+
+/// Remove the `l.facebook.com/l.php` redirect wrapper from a link, and drop internal links.
+///
+/// Relative links are resolved against `https://www.facebook.com/`, so they are internal unless
+/// they are redirects.
+fn unwrap_link<'a>(source: &'a str, href: &str) -> Option<Cow<'a, str>> {
+    let url = BASE_URL.join(href).ok()?;
+
+    match url.domain() {
+        Some("l.facebook.com" | "lm.facebook.com" | "www.facebook.com" | "facebook.com")
+            if url.path() == "/l.php" =>
+        {
+            url.query_pairs()
+                .find(|(key, _)| key == "u")
+                .map(|(_, value)| Cow::Owned(value.into_owned()))
+        }
+        Some("www.facebook.com" | "facebook.com") => None,
+        _ => resolve(source, href),
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct DeeplinkAdCard<'a> {
     #[serde(rename = "adArchiveID", with = "integer_or_integer_str")]
     pub ad_archive_id: u64,
+    #[serde(borrow)]
     pub snapshot: Snapshot<'a>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Snapshot<'a> {
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub title: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub link_url: Option<Cow<'a, str>>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub creation_time: DateTime<Utc>,
     #[serde(with = "integer_or_integer_str")]
     pub page_id: u64,
+    #[serde(borrow)]
     pub page_name: Cow<'a, str>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub current_page_name: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub page_profile_picture_url: Cow<'a, str>,
     pub page_entity_type: PageEntityType,
     pub page_is_profile_page: bool,
     pub page_like_count: usize,
+    #[serde(borrow)]
     pub instagram_url: Cow<'a, str>,
+    #[serde(borrow)]
     pub instagram_handle: Cow<'a, str>,
+    #[serde(borrow)]
     pub instagram_actor_name: Cow<'a, str>,
+    #[serde(borrow)]
     pub instagram_profile_pic_url: Cow<'a, str>,
+    #[serde(borrow)]
     pub videos: Vec<Video<'a>>,
 }
 
@@ -203,3 +378,50 @@ pub enum PageEntityType {
 }
 
 pub use super::Video;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKUP_01_EXAMPLE: &str = include_str!("../../../examples/library/v1-markup-01.html");
+
+    #[test]
+    fn parse_example_markup_01() {
+        let markup = Markup {
+            id: "u_0_1".into(),
+            html: MARKUP_01_EXAMPLE.into(),
+        };
+
+        let creative = markup.creative();
+
+        assert_eq!(
+            creative.body.as_deref(),
+            Some("Play chess against human-like bots.")
+        );
+        assert!(matches!(creative.title, Some(Cow::Borrowed("Chessiverse"))));
+        assert_eq!(creative.cta_text.as_deref(), Some("Learn more"));
+        assert_eq!(
+            creative.link_urls,
+            vec!["https://chessiverse.com/", "https://chessiverse.com/learn"]
+        );
+        assert!(matches!(creative.link_urls[1], Cow::Borrowed(_)));
+        assert_eq!(
+            creative.image_urls,
+            vec!["https://scontent.xx.fbcdn.net/image.jpg?stp=dst-jpg&_nc_cat=1"]
+        );
+        assert_eq!(
+            creative.video_urls,
+            vec!["https://video.xx.fbcdn.net/video.mp4"]
+        );
+    }
+
+    #[test]
+    fn other_buttons_are_not_cta() {
+        let markup = Markup {
+            id: "u_0_1".into(),
+            html: r#"<div><div data-ad-preview="message">Play chess</div><button>See ad details</button><div role="button">Menu</div></div>"#.into(),
+        };
+
+        assert_eq!(markup.creative().cta_text, None);
+    }
+}
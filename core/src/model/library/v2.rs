@@ -1,8 +1,14 @@
+use super::{Children, JsonSource};
+use bounded_static_derive_more::ToStatic;
 use chrono::{DateTime, Utc};
 use scraper_trail::archive::Archiveable;
-use serde_field_attributes::integer_str;
+use serde_field_attributes::{
+    integer_or_integer_str, integer_str, optional_integer_or_integer_str, represented_as_str,
+};
 use serde_json::Value;
 use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -10,42 +16,53 @@ pub enum Error {
     Json(#[from] serde_json::Error),
     #[error("Missing ad library field")]
     MissingAdLibraryMain,
+    #[error("Invalid range")]
+    InvalidRange(String),
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
 pub struct AdLibraryResponse<'a> {
     pub result: Option<AdLibraryResult<'a>>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize)]
 pub struct AdLibraryResult<'a> {
+    #[serde(borrow)]
     search_results_connection: RawSearchResultsConnection<'a>,
+    /// Missing from the GraphQL responses for further search pages.
+    #[serde(borrow, default)]
     deeplink_ad_archive_result: RawDeeplinkAdArchiveResult<'a>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, ToStatic, serde::Deserialize)]
 struct RawDeeplinkAdArchiveResult<'a> {
+    #[serde(borrow)]
     deeplink_ad_archive: Option<Ad<'a>>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize)]
 struct RawSearchResultsConnection<'a> {
     count: u64,
+    #[serde(borrow)]
     page_info: PageInfo<'a>,
+    #[serde(borrow)]
     edges: Vec<RawEdge<'a>>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize)]
 struct RawEdge<'a> {
+    #[serde(borrow)]
     node: RawNode<'a>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize)]
 struct RawNode<'a> {
+    #[serde(borrow)]
     collated_results: Vec<Ad<'a>>,
 }
 
 /// The search results extracted from the library page's embedded JSON scripts.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Serialize)]
 pub struct SearchResults<'a> {
     /// Total number of matching ads across all pages.
     pub count: u64,
@@ -76,44 +93,49 @@ impl<'a> AdLibraryResult<'a> {
         }
     }
 
-    pub fn extract(value: &Value) -> Result<Option<Self>, Error> {
-        let mut result: Option<Self> = None;
-
-        Self::extract_rec(value, &mut result)?;
-
-        Ok(result)
+    /// The ads in the search results, without cloning them.
+    pub fn search_result_ads(&self) -> impl Iterator<Item = &Ad<'a>> {
+        self.search_results_connection
+            .edges
+            .iter()
+            .flat_map(|edge| edge.node.collated_results.iter())
     }
 
-    fn extract_rec(value: &Value, out: &mut Option<Self>) -> Result<(), Error> {
-        if out.is_none() {
-            if let Some(array) = value.as_array() {
-                for item in array {
-                    Self::extract_rec(item, out)?;
-
-                    if out.is_some() {
-                        break;
-                    }
-                }
-            } else if let Some(object) = value.as_object() {
-                for (key, value) in object {
-                    if key == "ad_library_main"
-                        && let Ok(result) = serde_json::from_value::<Self>(value.clone())
-                    {
-                        *out = Some(result);
-
-                        break;
-                    }
+    /// Take ownership of the search results without cloning the ads.
+    #[must_use]
+    pub fn into_search_results(self) -> SearchResults<'a> {
+        SearchResults {
+            count: self.search_results_connection.count,
+            page_info: self.search_results_connection.page_info,
+            ads: self
+                .search_results_connection
+                .edges
+                .into_iter()
+                .flat_map(|edge| edge.node.collated_results)
+                .collect(),
+        }
+    }
 
-                    Self::extract_rec(value, out)?;
+    /// Find and parse the `ad_library_main` object (see [`JsonSource`] for how strings are handled).
+    pub fn extract<S: JsonSource<'a>>(value: S) -> Result<Option<Self>, Error> {
+        Ok(Self::extract_rec(value))
+    }
 
-                    if out.is_some() {
-                        break;
-                    }
+    fn extract_rec<S: JsonSource<'a>>(value: S) -> Option<Self> {
+        match value.children() {
+            Children::Array(mut items) => items.find_map(Self::extract_rec),
+            Children::Object(mut entries) => entries.find_map(|(key, value)| {
+                if key.as_ref() == "ad_library_main" {
+                    value
+                        .parse()
+                        .map_err(|error| ::log::warn!("Unable to parse ad_library_main: {error}"))
+                        .ok()
+                } else {
+                    Self::extract_rec(value)
                 }
-            }
+            }),
+            Children::None => None,
         }
-
-        Ok(())
     }
 }
 
@@ -130,75 +152,376 @@ impl Archiveable for AdLibraryResponse<'_> {
         )>,
         A::Error,
     > {
-        let next = map.next_entry::<
-            scraper_trail::archive::entry::Field,
-            scraper_trail::exchange::Response<'_, Value>,
-        >()?;
+        deserialize_result_field(map, |result| AdLibraryResponse { result })
+    }
+}
 
-        next.map(|(field, response)| {
-            response
-                .and_then(|data| AdLibraryResult::extract(&data))
-                .map(|response| (field, response.map(|result| Self { result })))
-        })
-        .map_or(Ok(None), |value| {
-            value.map_err(serde::de::Error::custom).map(Some)
-        })
+/// A page of results from a keyword search on the Ads Library web interface (either the initial
+/// page or a GraphQL pagination response).
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
+pub struct AdLibrarySearchResponse<'a> {
+    pub result: Option<AdLibraryResult<'a>>,
+}
+
+impl Archiveable for AdLibrarySearchResponse<'_> {
+    type RequestParams = crate::library::request::SearchRequestParams;
+
+    fn deserialize_response_field<'de, A: serde::de::MapAccess<'de>>(
+        _request_params: &Self::RequestParams,
+        map: &mut A,
+    ) -> Result<
+        Option<(
+            scraper_trail::archive::entry::Field,
+            scraper_trail::exchange::Response<'de, Self>,
+        )>,
+        A::Error,
+    > {
+        deserialize_result_field(map, |result| AdLibrarySearchResponse { result })
     }
 }
 
+fn deserialize_result_field<'de, A: serde::de::MapAccess<'de>, T>(
+    map: &mut A,
+    wrap: fn(Option<AdLibraryResult<'static>>) -> T,
+) -> Result<
+    Option<(
+        scraper_trail::archive::entry::Field,
+        scraper_trail::exchange::Response<'de, T>,
+    )>,
+    A::Error,
+> {
+    let next = map.next_entry::<
+        scraper_trail::archive::entry::Field,
+        scraper_trail::exchange::Response<'_, Value>,
+    >()?;
+
+    next.map(|(field, response)| {
+        response
+            .and_then(|mut data| AdLibraryResult::extract(&mut data).map(wrap))
+            .map(|response| (field, response))
+    })
+    .map_or(Ok(None), |value| {
+        value.map_err(serde::de::Error::custom).map(Some)
+    })
+}
+
 /// Pagination cursor returned alongside a page of search results.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct PageInfo<'a> {
+    #[serde(borrow)]
     pub end_cursor: Cow<'a, str>,
     pub has_next_page: bool,
 }
 
 /// A single ad entry from the library's `collated_results` array.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Ad<'a> {
     #[serde(with = "integer_str")]
     pub ad_archive_id: u64,
     /// Non-null when multiple creatives share the same ad.
     pub collation_count: Option<u64>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub collation_id: Option<Cow<'a, str>>,
     #[serde(with = "integer_str")]
     pub page_id: u64,
+    #[serde(borrow)]
     pub page_name: Cow<'a, str>,
     pub page_is_deleted: bool,
+    #[serde(borrow)]
     pub snapshot: Snapshot<'a>,
     pub is_active: bool,
     pub has_user_reported: bool,
     pub report_count: Option<u64>,
     /// Always an empty array in current responses.
     pub menu_items: Vec<Value>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub state_media_run_label: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub impressions_with_index: ImpressionsWithIndex<'a>,
-    pub gated_type: Cow<'a, str>,
-    pub categories: Vec<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub gated_type: GatedType<'a>,
+    #[serde(borrow)]
+    pub categories: Vec<AdCategory<'a>>,
     pub is_aaa_eligible: bool,
     pub contains_digital_created_media: bool,
-    pub reach_estimate: Option<Value>,
+    pub reach_estimate: Option<Estimate>,
+    #[serde(borrow)]
     pub currency: Cow<'a, str>,
-    pub spend: Option<Value>,
+    pub spend: Option<Estimate>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub end_date: DateTime<Utc>,
     pub publisher_platform: Vec<PublisherPlatform>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub start_date: DateTime<Utc>,
     pub contains_sensitive_content: bool,
-    pub total_active_time: Option<Value>,
+    pub total_active_time: Option<ActiveTime>,
     pub regional_regulation_data: RegionalRegulationData,
-    pub hide_data_status: Cow<'a, str>,
+    #[serde(borrow)]
+    pub hide_data_status: HideDataStatus<'a>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub fev_info: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub ad_id: Option<Cow<'a, str>>,
 }
 
-/// Impression count and relative popularity index for an ad.
+impl Ad<'_> {
+    /// Whether the ad is categorized as being about social issues, elections or politics.
+    #[must_use]
+    pub fn is_political(&self) -> bool {
+        self.categories.contains(&AdCategory::Political)
+    }
+
+    /// Whether the ad is in a special category (political, housing, employment, or financial).
+    ///
+    /// Unrecognized categories are treated as special, since `UNKNOWN` is used for ads without one.
+    #[must_use]
+    pub fn is_special_category(&self) -> bool {
+        self.categories
+            .iter()
+            .any(|category| *category != AdCategory::Unspecified)
+    }
+
+    /// Whether some of the ad's data has been hidden.
+    ///
+    /// Any status other than `NONE` (including unrecognized ones) is treated as hidden.
+    #[must_use]
+    pub fn is_data_hidden(&self) -> bool {
+        self.hide_data_status != HideDataStatus::NotHidden
+    }
+
+    /// Whether access to the ad's details is restricted.
+    #[must_use]
+    pub fn is_gated(&self) -> bool {
+        self.gated_type != GatedType::Eligible
+    }
+
+    /// The spend range together with the ad's currency, if both are available.
+    #[must_use]
+    pub fn spend_amount(&self) -> Option<Amount<'_>> {
+        self.spend
+            .as_ref()
+            .and_then(Estimate::range)
+            .filter(|_| !self.currency.is_empty())
+            .map(|range| Amount {
+                range,
+                currency: &self.currency,
+            })
+    }
+
+    /// The time the ad has been active, if provided in a recognized shape.
+    #[must_use]
+    pub fn active_duration(&self) -> Option<std::time::Duration> {
+        self.total_active_time
+            .as_ref()
+            .and_then(ActiveTime::duration)
+    }
+}
+
+/// An estimated quantity (reach or spend), in one of the shapes the library uses.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum Estimate {
+    Exact(#[serde(with = "integer_or_integer_str")] u64),
+    Range(Range),
+    /// A textual range such as `"100 - 199"` or `">1M"`.
+    Text(#[serde(with = "represented_as_str")] Range),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl Estimate {
+    #[must_use]
+    pub const fn range(&self) -> Option<Range> {
+        match self {
+            Self::Exact(value) => Some(Range::exact(*value)),
+            Self::Range(range) | Self::Text(range) => Some(*range),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// An inclusive range; a missing upper bound means the range is open-ended.
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    #[serde(with = "integer_or_integer_str")]
+    pub lower_bound: u64,
+    #[serde(
+        with = "serde_field_attributes::optional_integer_or_integer_str",
+        default
+    )]
+    pub upper_bound: Option<u64>,
+}
+
+impl Range {
+    #[must_use]
+    pub const fn exact(value: u64) -> Self {
+        Self {
+            lower_bound: value,
+            upper_bound: Some(value),
+        }
+    }
+
+    /// Parse a bound, returning the integers at or below and at or above it (which differ if it
+    /// has a decimal part), so that the range can always include the value.
+    fn parse_bound(s: &str) -> Option<(u64, u64)> {
+        let s = s.trim();
+        let (number, multiplier) = match s.chars().last()?.to_ascii_uppercase() {
+            'K' => (&s[..s.len() - 1], 1_000),
+            'M' => (&s[..s.len() - 1], 1_000_000),
+            'B' => (&s[..s.len() - 1], 1_000_000_000),
+            _ => (s, 1),
+        };
+
+        let number = normalize_number(number, multiplier > 1);
+        let (integer, fraction) = number.split_once('.').unwrap_or((&number, ""));
+
+        if integer.is_empty() && fraction.is_empty() {
+            return None;
+        }
+
+        // Integer arithmetic keeps values like `1.1K` exact.
+        let scale = 10_u128.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+        let scaled = format!("{integer}{fraction}")
+            .parse::<u128>()
+            .ok()?
+            .checked_mul(multiplier)?;
+
+        Some((
+            u64::try_from(scaled / scale).ok()?,
+            u64::try_from(scaled.div_ceil(scale)).ok()?,
+        ))
+    }
+}
+
+/// Strip currency symbols and thousands separators from a number, normalizing the decimal
+/// separator (which may be `.` or `,`, depending on the locale) to `.`.
+///
+/// A single separator followed by exactly three digits (as in `1.000` or `1,000`) is a thousands
+/// separator unless the number has a multiplier (as in `1,5K`).
+fn normalize_number(number: &str, scaled: bool) -> String {
+    let chars = number
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect::<Vec<_>>();
+
+    let decimal = chars
+        .iter()
+        .rposition(|c| !c.is_ascii_digit())
+        .filter(|&last| {
+            let separator = chars[last];
+            let mixed = chars[..last]
+                .iter()
+                .any(|c| !c.is_ascii_digit() && *c != separator);
+            let repeated = chars[..last].contains(&separator);
+
+            mixed || (!repeated && (scaled || chars.len() - last - 1 != 3))
+        });
+
+    chars
+        .iter()
+        .enumerate()
+        .filter_map(|(index, c)| {
+            if c.is_ascii_digit() {
+                Some(*c)
+            } else {
+                (Some(index) == decimal).then_some('.')
+            }
+        })
+        .collect()
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.upper_bound {
+            Some(upper_bound) if upper_bound == self.lower_bound => {
+                write!(f, "{upper_bound}")
+            }
+            Some(upper_bound) => write!(f, "{} - {upper_bound}", self.lower_bound),
+            None => write!(f, ">{}", self.lower_bound),
+        }
+    }
+}
+
+impl FromStr for Range {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || Error::InvalidRange(s.to_string());
+        let trimmed = s.trim();
+
+        if let Some(upper_bound) = trimmed.strip_prefix('<') {
+            let (_, upper_bound) = Self::parse_bound(upper_bound).ok_or_else(error)?;
+
+            Ok(Self {
+                lower_bound: 0,
+                upper_bound: Some(upper_bound.saturating_sub(1)),
+            })
+        } else if let Some(lower_bound) = trimmed
+            .strip_prefix('>')
+            .or_else(|| trimmed.strip_suffix('+'))
+        {
+            let (lower_bound, _) = Self::parse_bound(lower_bound).ok_or_else(error)?;
+
+            Ok(Self {
+                lower_bound,
+                upper_bound: None,
+            })
+        } else if let Some((lower_bound, upper_bound)) = trimmed.split_once('-') {
+            let (lower_bound, _) = Self::parse_bound(lower_bound).ok_or_else(error)?;
+            let (_, upper_bound) = Self::parse_bound(upper_bound).ok_or_else(error)?;
+
+            Ok(Self {
+                lower_bound,
+                upper_bound: Some(upper_bound),
+            })
+        } else {
+            let (lower_bound, upper_bound) = Self::parse_bound(trimmed).ok_or_else(error)?;
+
+            Ok(Self {
+                lower_bound,
+                upper_bound: Some(upper_bound),
+            })
+        }
+    }
+}
+
+/// A spend range in a specific currency.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Amount<'a> {
+    pub range: Range,
+    /// ISO 4217 currency code.
+    pub currency: &'a str,
+}
+
+/// How long an ad has been active.
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ActiveTime {
+    Seconds(#[serde(with = "integer_or_integer_str")] u64),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl ActiveTime {
+    #[must_use]
+    pub const fn duration(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::Seconds(seconds) => Some(std::time::Duration::from_secs(*seconds)),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// Impression count and relative popularity index for an ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ImpressionsWithIndex<'a> {
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub impressions_text: Option<Cow<'a, str>>,
     /// Relative popularity index; sometimes `-1` when no impression data is available.
     pub impressions_index: Option<i64>,
@@ -228,80 +551,621 @@ pub struct TwAntiScamData {
 }
 
 /// The creative content of an ad, including its media, copy, and page metadata.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Snapshot<'a> {
-    pub branded_content: Option<Value>,
+    #[serde(borrow)]
+    pub branded_content: Option<BrandedContent<'a>>,
     #[serde(with = "integer_str")]
     pub page_id: u64,
     pub page_is_deleted: bool,
+    #[serde(borrow)]
     pub page_profile_uri: Cow<'a, str>,
-    pub root_reshared_post: Option<Value>,
+    #[serde(borrow)]
+    pub root_reshared_post: Option<ResharedPost<'a>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub byline: Option<Cow<'a, str>>,
-    pub disclaimer_label: Option<Value>,
+    #[serde(borrow)]
+    pub disclaimer_label: Option<Disclaimer<'a>>,
+    #[serde(borrow)]
     pub page_name: Cow<'a, str>,
+    #[serde(borrow)]
     pub page_profile_picture_url: Cow<'a, str>,
-    pub event: Option<Value>,
+    #[serde(borrow)]
+    pub event: Option<Event<'a>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub caption: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub cta_text: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub cards: Vec<Card<'a>>,
+    #[serde(borrow)]
     pub body: Option<Body<'a>>,
     pub cta_type: Option<CtaType>,
     pub display_format: Option<DisplayFormat>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub link_description: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub link_url: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub images: Vec<Image<'a>>,
-    pub page_categories: Vec<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub page_categories: Vec<PageCategory<'a>>,
     pub page_like_count: Option<usize>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub title: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub videos: Vec<super::Video<'a>>,
     pub is_reshared: Option<bool>,
-    pub extra_links: Vec<Value>,
-    pub extra_texts: Vec<Value>,
-    pub extra_images: Vec<Value>,
-    pub extra_videos: Vec<Value>,
-    pub country_iso_code: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub extra_links: Vec<ExtraLink<'a>>,
+    #[serde(borrow)]
+    pub extra_texts: Vec<ExtraText<'a>>,
+    #[serde(borrow)]
+    pub extra_images: Vec<ExtraImage<'a>>,
+    #[serde(borrow)]
+    pub extra_videos: Vec<super::Video<'a>>,
+    #[serde(borrow)]
+    pub country_iso_code: Option<crate::model::country::Country<'a>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub brazil_tax_id: Option<Cow<'a, str>>,
-    pub additional_info: Option<Value>,
+    /// Always `null` in the pages we've seen.
+    pub additional_info: Option<super::Unparsed>,
     pub ec_certificates: Vec<Value>,
 }
 
 /// The primary text body of a snapshot.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Body<'a> {
+    #[serde(borrow)]
     pub text: Cow<'a, str>,
 }
 
+impl<'a> Snapshot<'a> {
+    /// Whether the snapshot or any of its cards contains `{{...}}` placeholder copy.
+    #[must_use]
+    pub fn is_templated(&self) -> bool {
+        let snapshot_texts = [
+            self.body.as_ref().map(|body| body.text.as_ref()),
+            self.title.as_deref(),
+            self.caption.as_deref(),
+            self.link_description.as_deref(),
+            self.cta_text.as_deref(),
+        ];
+
+        let card_texts = self.cards.iter().flat_map(|card| {
+            [
+                card.body.as_deref(),
+                card.title.as_deref(),
+                card.caption.as_deref(),
+                card.link_description.as_deref(),
+                card.cta_text.as_deref(),
+            ]
+        });
+
+        snapshot_texts
+            .into_iter()
+            .chain(card_texts)
+            .flatten()
+            .any(super::template::has_placeholders)
+    }
+
+    /// One concrete creative per card, with placeholders substituted from card data.
+    #[must_use]
+    pub fn expand_templates(&self) -> Vec<super::template::ConcreteCreative<'_>> {
+        super::template::expand(self)
+    }
+
+    /// Whether any of the advertiser page's categories is political.
+    #[must_use]
+    pub fn has_political_page(&self) -> bool {
+        self.page_categories.iter().any(PageCategory::is_political)
+    }
+
+    /// All dynamic creative variants, in the order links, texts, images, videos.
+    pub fn extra_assets(&self) -> impl Iterator<Item = ExtraAsset<'_, 'a>> {
+        self.extra_links
+            .iter()
+            .map(ExtraAsset::Link)
+            .chain(self.extra_texts.iter().map(ExtraAsset::Text))
+            .chain(self.extra_images.iter().map(ExtraAsset::Image))
+            .chain(self.extra_videos.iter().map(ExtraAsset::Video))
+    }
+
+    /// Whether the ad is a paid partnership with another page (e.g. an influencer).
+    #[must_use]
+    pub const fn is_branded_content(&self) -> bool {
+        self.branded_content.is_some()
+    }
+
+    /// The partner page for branded content, if it could be identified.
+    #[must_use]
+    pub const fn branded_content_partner(&self) -> Option<&BrandedContentPartner<'_>> {
+        match &self.branded_content {
+            Some(BrandedContent::Partner(partner)) => Some(partner),
+            _ => None,
+        }
+    }
+
+    /// The promoted event, if it could be identified.
+    #[must_use]
+    pub const fn event_details(&self) -> Option<&EventDetails<'_>> {
+        match &self.event {
+            Some(Event::Details(details)) => Some(details),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn disclaimer_text(&self) -> Option<&str> {
+        self.disclaimer_label.as_ref().and_then(Disclaimer::text)
+    }
+
+    /// The original post, if this ad is a reshare and the post could be identified.
+    #[must_use]
+    pub const fn reshared_post(&self) -> Option<&ResharedPostDetails<'_>> {
+        match &self.root_reshared_post {
+            Some(ResharedPost::Details(details)) => Some(details),
+            _ => None,
+        }
+    }
+}
+
+/// The branded content (paid partnership) information attached to a snapshot.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum BrandedContent<'a> {
+    #[serde(borrow)]
+    Partner(BrandedContentPartner<'a>),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+/// The partner page in a branded content ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+pub struct BrandedContentPartner<'a> {
+    #[serde(with = "integer_or_integer_str")]
+    pub page_id: u64,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
+    pub page_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
+    pub current_page_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
+    pub page_profile_uri: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
+    pub instagram_handle: Option<Cow<'a, str>>,
+}
+
+impl BrandedContentPartner<'_> {
+    /// The most recent name of the partner page.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.current_page_name
+            .as_deref()
+            .or(self.page_name.as_deref())
+    }
+}
+
+/// An event promoted by an ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum Event<'a> {
+    #[serde(borrow)]
+    Details(EventDetails<'a>),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+pub struct EventDetails<'a> {
+    #[serde(with = "optional_integer_or_integer_str", default)]
+    pub id: Option<u64>,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(with = "chrono::serde::ts_seconds_option", default)]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option", default)]
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(borrow, default)]
+    pub location: Option<EventLocation<'a>>,
+}
+
+/// An event location, which may be a bare name or a place object.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum EventLocation<'a> {
+    #[serde(borrow)]
+    Name(Cow<'a, str>),
+    Place {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        city: Option<Cow<'a, str>>,
+    },
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl EventLocation<'_> {
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Name(name) | Self::Place { name, .. } => Some(name),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// Disclaimer text (e.g. "Paid for by").
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum Disclaimer<'a> {
+    #[serde(borrow)]
+    Text(Cow<'a, str>),
+    Labeled {
+        #[serde(borrow)]
+        text: Cow<'a, str>,
+    },
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl Disclaimer<'_> {
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::Labeled { text } => Some(text),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// The original post for a reshared ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ResharedPost<'a> {
+    #[serde(borrow)]
+    Details(ResharedPostDetails<'a>),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+pub struct ResharedPostDetails<'a> {
+    #[serde(with = "integer_or_integer_str")]
+    pub id: u64,
+    #[serde(with = "optional_integer_or_integer_str", default)]
+    pub page_id: Option<u64>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
+    pub page_name: Option<Cow<'a, str>>,
+}
+
+/// A reference to one of a snapshot's dynamic creative variant assets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExtraAsset<'s, 'a> {
+    Link(&'s ExtraLink<'a>),
+    Text(&'s ExtraText<'a>),
+    Image(&'s ExtraImage<'a>),
+    Video(&'s super::Video<'a>),
+}
+
+/// A link variant in a dynamic creative ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ExtraLink<'a> {
+    #[serde(borrow)]
+    Url(Cow<'a, str>),
+    Link {
+        #[serde(borrow)]
+        link_url: Cow<'a, str>,
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        title: Option<Cow<'a, str>>,
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        caption: Option<Cow<'a, str>>,
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        link_description: Option<Cow<'a, str>>,
+    },
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl ExtraLink<'_> {
+    #[must_use]
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Self::Url(link_url) | Self::Link { link_url, .. } => Some(link_url),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// A body text variant in a dynamic creative ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ExtraText<'a> {
+    #[serde(borrow)]
+    Text(Cow<'a, str>),
+    Body {
+        #[serde(borrow)]
+        text: Cow<'a, str>,
+    },
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl ExtraText<'_> {
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::Body { text } => Some(text),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// An image variant in a dynamic creative ad.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ExtraImage<'a> {
+    #[serde(borrow)]
+    Image(Image<'a>),
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl<'a> ExtraImage<'a> {
+    #[must_use]
+    pub const fn image(&self) -> Option<&Image<'a>> {
+        match self {
+            Self::Image(image) => Some(image),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+/// A crop of an image for a particular placement, in pixels from the top left.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An image crop, given either as a rectangle or as a pair of corner points.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum ImageCrop<'a> {
+    Rect {
+        /// The placement aspect ratio key (e.g. `"191x100"`).
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        key: Option<Cow<'a, str>>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    Points {
+        #[serde(borrow, default, with = "super::optional_cow_str")]
+        key: Option<Cow<'a, str>>,
+        /// Top-left and bottom-right corners.
+        points: [[u32; 2]; 2],
+    },
+    /// Fallback for shapes we don't recognize yet.
+    Raw(super::Unparsed),
+}
+
+impl ImageCrop<'_> {
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Rect { key, .. } | Self::Points { key, .. } => key.as_deref(),
+            Self::Raw(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn rect(&self) -> Option<CropRect> {
+        match self {
+            Self::Rect {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => Some(CropRect {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            }),
+            Self::Points {
+                points: [[left, top], [right, bottom]],
+                ..
+            } => Some(CropRect {
+                x: *left,
+                y: *top,
+                width: right.saturating_sub(*left),
+                height: bottom.saturating_sub(*top),
+            }),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
 /// A single card in a carousel or dynamic product ad.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Card<'a> {
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub body: Option<Cow<'a, str>>,
     pub cta_type: Option<CtaType>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub caption: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub link_description: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub link_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub title: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub cta_text: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub video_hd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub video_preview_image_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub video_sd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub watermarked_video_hd_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub watermarked_video_sd_url: Option<Cow<'a, str>>,
-    pub image_crops: Vec<Value>,
+    #[serde(borrow)]
+    pub image_crops: Vec<ImageCrop<'a>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub original_image_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub resized_image_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, with = "super::optional_cow_str")]
     pub watermarked_resized_image_url: Option<Cow<'a, str>>,
 }
 
 /// A standalone image asset attached to a snapshot.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 pub struct Image<'a> {
-    pub image_crops: Vec<Value>,
+    #[serde(borrow)]
+    pub image_crops: Vec<ImageCrop<'a>>,
+    #[serde(borrow)]
     pub original_image_url: Cow<'a, str>,
+    #[serde(borrow)]
     pub resized_image_url: Cow<'a, str>,
+    #[serde(borrow)]
     pub watermarked_resized_image_url: Cow<'a, str>,
 }
 
+/// Define an enum for a string field with a set of known values, keeping unrecognized values in an
+/// `Other` variant (known values are matched case-insensitively).
+macro_rules! labelled_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $label:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, ToStatic)]
+        pub enum $name<'a> {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value we don't recognize.
+            Other(Cow<'a, str>),
+        }
+
+        impl $name<'_> {
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $label,)*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<Cow<'a, str>> for $name<'a> {
+            fn from(value: Cow<'a, str>) -> Self {
+                $(
+                    if value.eq_ignore_ascii_case($label) {
+                        return Self::$variant;
+                    }
+                )*
+
+                Self::Other(value)
+            }
+        }
+
+        impl Display for $name<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de: 'a, 'a> serde::Deserialize<'de> for $name<'a> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <crate::model::BorrowedStr<'a> as serde::Deserialize>::deserialize(deserializer)
+                    .map(|crate::model::BorrowedStr(value)| Self::from(value))
+            }
+        }
+    };
+}
+
+labelled_enum! {
+    /// The special ad category of an ad.
+    pub enum AdCategory {
+        /// Social issues, elections or politics.
+        Political => "POLITICAL",
+        Housing => "HOUSING",
+        Employment => "EMPLOYMENT",
+        Credit => "CREDIT",
+        FinancialProductsServices => "FINANCIAL_PRODUCTS_SERVICES",
+        /// No special category (sent by the library as `UNKNOWN`).
+        Unspecified => "UNKNOWN",
+    }
+}
+
+labelled_enum! {
+    /// Whether the ad's details are available to all viewers.
+    pub enum GatedType {
+        Eligible => "ELIGIBLE",
+        AgeGated => "AGE_GATED",
+    }
+}
+
+labelled_enum! {
+    /// Whether some of the ad's data has been hidden from the library.
+    pub enum HideDataStatus {
+        NotHidden => "NONE",
+        Hidden => "HIDDEN",
+    }
+}
+
+labelled_enum! {
+    /// The category of the advertiser's page.
+    ///
+    /// These are human-readable labels, and there are many of them, so only the political and
+    /// media ones are listed.
+    pub enum PageCategory {
+        Politician => "Politician",
+        PoliticalCandidate => "Political Candidate",
+        PoliticalParty => "Political Party",
+        PoliticalOrganization => "Political Organization",
+        GovernmentOfficial => "Government Official",
+        GovernmentOrganization => "Government Organization",
+        NonprofitOrganization => "Nonprofit Organization",
+        MediaNewsCompany => "Media/News Company",
+        NewsAndMediaWebsite => "News & Media Website",
+    }
+}
+
+impl PageCategory<'_> {
+    /// Whether the page belongs to a politician, party, or government body.
+    #[must_use]
+    pub const fn is_political(&self) -> bool {
+        matches!(
+            self,
+            Self::Politician
+                | Self::PoliticalCandidate
+                | Self::PoliticalParty
+                | Self::PoliticalOrganization
+                | Self::GovernmentOfficial
+                | Self::GovernmentOrganization
+        )
+    }
+}
+
 /// The platforms on which an ad ran.
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
 pub enum PublisherPlatform {
@@ -334,6 +1198,19 @@ pub enum DisplayFormat {
     Unknown,
 }
 
+impl DisplayFormat {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "IMAGE",
+            Self::Video => "VIDEO",
+            Self::Dpa => "DPA",
+            Self::Dco => "DCO",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
 /// The call-to-action type for a snapshot or card.
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
 pub enum CtaType {
@@ -358,3 +1235,301 @@ pub enum CtaType {
     #[serde(other)]
     Unknown,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bounded_static::ToBoundedStatic;
+
+    const AD_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-ad-01.json");
+    const SEARCH_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-search-01.json");
+
+    fn search_01_ads() -> Result<Vec<Ad<'static>>, Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(SEARCH_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+
+        Ok(result.into_search_results().ads.to_static())
+    }
+
+    #[test]
+    fn extract_example_ad_01() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(AD_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let ad = result.ad().expect("deeplink ad");
+
+        assert_eq!(ad.ad_archive_id, 576_535_441_426_103);
+        assert_eq!(ad.snapshot.display_format, Some(DisplayFormat::Image));
+        assert!(result.search_results().ads.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn extract_borrows_from_input() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(AD_01_EXAMPLE)?;
+        let result = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+        let ad = result.ad().expect("deeplink ad");
+
+        assert!(matches!(ad.page_name, Cow::Borrowed(_)));
+        assert!(matches!(ad.snapshot.link_url, Some(Cow::Borrowed(_))));
+
+        let owned: Ad<'static> = ad.to_static();
+        assert_eq!(&owned, ad);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extract_takes_strings_from_owned_input() -> Result<(), Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(AD_01_EXAMPLE)?;
+        let borrowed = AdLibraryResult::extract(&value)?.expect("ad_library_main");
+
+        let mut owned_value = value.clone();
+        let owned: AdLibraryResult<'static> =
+            AdLibraryResult::extract(&mut owned_value)?.expect("ad_library_main");
+
+        assert_eq!(owned, borrowed);
+        assert!(matches!(
+            owned.ad().map(|ad| &ad.page_name),
+            Some(Cow::Owned(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn estimate_variants() -> Result<(), Box<dyn std::error::Error>> {
+        let ads = search_01_ads()?;
+
+        assert_eq!(ads.len(), 5);
+
+        assert_eq!(ads[0].reach_estimate, None);
+        assert_eq!(ads[0].spend_amount(), None);
+
+        assert_eq!(ads[1].reach_estimate, Some(Estimate::Exact(12_000)));
+        assert_eq!(
+            ads[1].spend_amount(),
+            Some(Amount {
+                range: Range {
+                    lower_bound: 100,
+                    upper_bound: Some(199)
+                },
+                currency: "EUR"
+            })
+        );
+        assert_eq!(
+            ads[1].active_duration(),
+            Some(std::time::Duration::from_hours(336))
+        );
+
+        assert_eq!(
+            ads[2].reach_estimate.as_ref().and_then(Estimate::range),
+            Some(Range {
+                lower_bound: 1_000,
+                upper_bound: Some(5_000)
+            })
+        );
+        assert_eq!(
+            ads[2].spend.as_ref().and_then(Estimate::range),
+            Some(Range {
+                lower_bound: 100,
+                upper_bound: Some(199)
+            })
+        );
+        assert_eq!(
+            ads[2].active_duration(),
+            Some(std::time::Duration::from_hours(24))
+        );
+
+        assert_eq!(
+            ads[3].reach_estimate,
+            Some(Estimate::Range(Range {
+                lower_bound: 1_000_000,
+                upper_bound: None
+            }))
+        );
+        assert_eq!(
+            ads[3].spend.as_ref().and_then(Estimate::range),
+            Some(Range {
+                lower_bound: 0,
+                upper_bound: Some(99)
+            })
+        );
+
+        assert!(matches!(ads[4].reach_estimate, Some(Estimate::Raw(_))));
+        assert!(matches!(ads[4].spend, Some(Estimate::Raw(_))));
+        assert!(matches!(ads[4].total_active_time, Some(ActiveTime::Raw(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_range_text() {
+        assert_eq!("1.5K+".parse::<Range>().unwrap().lower_bound, 1_500);
+        assert_eq!(
+            ">1M".parse::<Range>().unwrap(),
+            Range {
+                lower_bound: 1_000_000,
+                upper_bound: None
+            }
+        );
+        assert_eq!("1,234".parse::<Range>().unwrap(), Range::exact(1_234));
+        assert_eq!("1.000".parse::<Range>().unwrap(), Range::exact(1_000));
+        assert_eq!(
+            "1.000 - 4.999".parse::<Range>().unwrap(),
+            Range {
+                lower_bound: 1_000,
+                upper_bound: Some(4_999)
+            }
+        );
+        assert_eq!(
+            "1.234.567".parse::<Range>().unwrap(),
+            Range::exact(1_234_567)
+        );
+        assert_eq!("1,5K".parse::<Range>().unwrap(), Range::exact(1_500));
+        assert_eq!("1.1K".parse::<Range>().unwrap(), Range::exact(1_100));
+
+        // Decimal amounts are kept within the range instead of being rounded.
+        let decimal = Range {
+            lower_bound: 1_234,
+            upper_bound: Some(1_235),
+        };
+
+        assert_eq!("1 234,50 €".parse::<Range>().unwrap(), decimal);
+        assert_eq!("€1,234.50".parse::<Range>().unwrap(), decimal);
+        assert_eq!(
+            "99,50 € - 199,50 €".parse::<Range>().unwrap(),
+            Range {
+                lower_bound: 99,
+                upper_bound: Some(200),
+            }
+        );
+        assert_eq!("<0.5K".parse::<Range>().unwrap().upper_bound, Some(499));
+        assert!("n/a".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn range_display_roundtrip() {
+        for text in ["100 - 199", ">1000", "42"] {
+            assert_eq!(text.parse::<Range>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn snapshot_extras() -> Result<(), Box<dyn std::error::Error>> {
+        let ads = search_01_ads()?;
+        let snapshot = &ads[1].snapshot;
+
+        let partner = snapshot.branded_content_partner().expect("partner");
+        assert_eq!(partner.page_id, 100_064_758_934_672);
+        assert_eq!(partner.name(), Some("Magnus Chess Club"));
+
+        let event = snapshot.event_details().expect("event");
+        assert_eq!(event.name, "Berlin Chess Open");
+        assert_eq!(event.id, Some(1_023_456_789));
+        assert_eq!(
+            event.location.as_ref().and_then(EventLocation::name),
+            Some("Alexanderplatz")
+        );
+
+        assert_eq!(
+            snapshot.disclaimer_text(),
+            Some("Paid for by Chessiverse GmbH")
+        );
+
+        let post = snapshot.reshared_post().expect("reshared post");
+        assert_eq!(post.id, 987_654_321);
+        assert_eq!(post.page_name.as_deref(), Some("Chess Daily"));
+
+        let other = &ads[4].snapshot;
+        assert!(other.is_branded_content());
+        assert!(other.branded_content_partner().is_none());
+        assert_eq!(other.disclaimer_text(), Some("Paid for by Someone Else"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_creative_assets() -> Result<(), Box<dyn std::error::Error>> {
+        let ads = search_01_ads()?;
+        let snapshot = &ads[2].snapshot;
+
+        assert_eq!(snapshot.display_format, Some(DisplayFormat::Dco));
+        assert_eq!(snapshot.extra_assets().count(), 6);
+        assert_eq!(
+            snapshot.extra_links[1].url(),
+            Some("https://chessiverse.com/learn")
+        );
+        assert_eq!(
+            snapshot.extra_texts[0].text(),
+            Some("Train with bots that play like humans.")
+        );
+
+        let crop = &snapshot.extra_images[0].image().expect("image").image_crops[0];
+        assert_eq!(crop.key(), Some("100x100"));
+        assert_eq!(
+            crop.rect(),
+            Some(CropRect {
+                x: 140,
+                y: 0,
+                width: 600,
+                height: 600
+            })
+        );
+        assert_eq!(
+            snapshot.images[0].image_crops[0].rect(),
+            Some(CropRect {
+                x: 0,
+                y: 0,
+                width: 600,
+                height: 314
+            })
+        );
+
+        let unexpected: ExtraImage<'_> =
+            serde_json::from_str(r#"{"original_image_url": null, "resized_image_url": null}"#)?;
+        assert!(matches!(unexpected, ExtraImage::Raw(_)));
+        assert_eq!(unexpected.image(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn categories_and_statuses() -> Result<(), Box<dyn std::error::Error>> {
+        let ads = search_01_ads()?;
+
+        assert!(!ads[0].is_political());
+        assert!(!ads[0].is_special_category());
+        assert!(!ads[0].is_data_hidden());
+        assert!(!ads[0].is_gated());
+        assert_eq!(
+            ads[0].snapshot.page_categories,
+            vec![PageCategory::Other("Video game".into())]
+        );
+
+        assert!(ads[3].is_political());
+        assert!(ads[3].is_data_hidden());
+        assert!(ads[3].snapshot.has_political_page());
+        assert_eq!(
+            ads[3].snapshot.page_categories[0],
+            PageCategory::PoliticalParty
+        );
+        assert_eq!(
+            serde_json::to_string(&ads[3].snapshot.page_categories)?,
+            r#"["Political Party","Community"]"#
+        );
+
+        assert_eq!(ads[4].gated_type, GatedType::Other("SOME_NEW_GATE".into()));
+        assert_eq!(ads[4].gated_type.as_str(), "SOME_NEW_GATE");
+        assert_eq!(
+            serde_json::from_str::<Vec<AdCategory<'_>>>(r#"["UNKNOWN","NEW_CATEGORY"]"#)?,
+            vec![
+                AdCategory::Unspecified,
+                AdCategory::Other("NEW_CATEGORY".into())
+            ]
+        );
+        assert!(ads[4].is_gated());
+
+        Ok(())
+    }
+}
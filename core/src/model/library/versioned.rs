@@ -0,0 +1,266 @@
+//! Library pages in either the current (v2) or the older (v1) format, detected automatically.
+
+use super::{v1, v2};
+use bounded_static_derive_more::ToStatic;
+use scraper_trail::archive::Archiveable;
+use serde_json::Value;
+use std::fmt::Display;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Older library page format error: {0}")]
+    V1(#[from] v1::Error),
+    #[error("Library page format error: {0}")]
+    V2(#[from] v2::Error),
+}
+
+/// The format of a library page.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Format {
+    /// Ad markup and a `deeplinkAdCard` snapshot, used before Meta's page change.
+    V1,
+    /// An `ad_library_main` object.
+    V2,
+}
+
+impl Format {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A library page's JSON scripts, parsed with whichever extractor matched.
+#[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
+pub enum LibraryResponse<'a> {
+    V1(Box<v1::Ad<'a>>),
+    V2(Box<v2::AdLibraryResult<'a>>),
+    /// Neither format was found (e.g. for an empty or blocked page).
+    Empty,
+}
+
+impl<'a> LibraryResponse<'a> {
+    /// Try the v2 extractor, falling back to the v1 extractor if there is no `ad_library_main`.
+    ///
+    /// Pages with only parts of a v1 ad (e.g. markup without a snapshot) are errors.
+    pub fn extract(value: &'a Value) -> Result<Self, Error> {
+        Self::from_results(v2::AdLibraryResult::extract(value)?, || {
+            v1::Ad::extract(value)
+        })
+    }
+
+    fn from_results<F: FnOnce() -> Result<Option<v1::Ad<'a>>, v1::Error>>(
+        v2_result: Option<v2::AdLibraryResult<'a>>,
+        v1_extract: F,
+    ) -> Result<Self, Error> {
+        if let Some(result) = v2_result {
+            return Ok(Self::V2(Box::new(result)));
+        }
+
+        match v1_extract() {
+            Ok(Some(ad)) => Ok(Self::V1(Box::new(ad))),
+            // Pages without any v1 markup are only empty.
+            Ok(None) | Err(v1::Error::MissingMarkupElement) => Ok(Self::Empty),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// The format that matched (if any).
+    #[must_use]
+    pub const fn format(&self) -> Option<Format> {
+        match self {
+            Self::V1(_) => Some(Format::V1),
+            Self::V2(_) => Some(Format::V2),
+            Self::Empty => None,
+        }
+    }
+
+    /// The ad the page is for (if it was found).
+    #[must_use]
+    pub fn ad(&self) -> Option<AdSummary<'_>> {
+        match self {
+            Self::V1(ad) => Some(AdSummary::from_v1(ad)),
+            Self::V2(result) => result.ad().map(AdSummary::from_v2),
+            Self::Empty => None,
+        }
+    }
+
+    /// The page's ad followed by any search results it includes (only for v2 pages).
+    #[must_use]
+    pub fn ads(&self) -> Vec<AdSummary<'_>> {
+        let mut ads = self.ad().into_iter().collect::<Vec<_>>();
+
+        if let Self::V2(result) = self {
+            ads.extend(result.search_result_ads().map(AdSummary::from_v2));
+        }
+
+        ads
+    }
+}
+
+impl LibraryResponse<'static> {
+    /// Like [`LibraryResponse::extract`], but taking strings out of the value instead of copying
+    /// them.
+    pub fn extract_owned(value: &mut Value) -> Result<Self, Error> {
+        let v2_result = v2::AdLibraryResult::extract(&mut *value)?;
+
+        Self::from_results(v2_result, || v1::Ad::extract(value))
+    }
+}
+
+/// The fields shared by ads in both formats.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AdSummary<'a> {
+    pub format: Format,
+    pub ad_id: u64,
+    pub page_id: u64,
+    pub page_name: &'a str,
+    pub link_url: Option<&'a str>,
+}
+
+impl<'a> AdSummary<'a> {
+    #[must_use]
+    pub fn from_v1(ad: &'a v1::Ad<'_>) -> Self {
+        let snapshot = &ad.deeplink_ad_card.snapshot;
+
+        Self {
+            format: Format::V1,
+            ad_id: ad.deeplink_ad_card.ad_archive_id,
+            page_id: snapshot.page_id,
+            page_name: &snapshot.page_name,
+            link_url: snapshot.link_url.as_deref(),
+        }
+    }
+
+    #[must_use]
+    pub fn from_v2(ad: &'a v2::Ad<'_>) -> Self {
+        Self {
+            format: Format::V2,
+            ad_id: ad.ad_archive_id,
+            page_id: ad.snapshot.page_id,
+            page_name: &ad.page_name,
+            link_url: ad.snapshot.link_url.as_deref(),
+        }
+    }
+}
+
+impl Archiveable for LibraryResponse<'_> {
+    type RequestParams = crate::library::request::Params;
+
+    fn deserialize_response_field<'de, A: serde::de::MapAccess<'de>>(
+        _request_params: &Self::RequestParams,
+        map: &mut A,
+    ) -> Result<
+        Option<(
+            scraper_trail::archive::entry::Field,
+            scraper_trail::exchange::Response<'de, Self>,
+        )>,
+        A::Error,
+    > {
+        let next = map.next_entry::<
+            scraper_trail::archive::entry::Field,
+            scraper_trail::exchange::Response<'_, Value>,
+        >()?;
+
+        next.map(|(field, response)| {
+            response
+                .and_then(|mut data| LibraryResponse::extract_owned(&mut data))
+                .map(|response| (field, response))
+        })
+        .map_or(Ok(None), |value| {
+            value.map_err(serde::de::Error::custom).map(Some)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AD_01_EXAMPLE: &str = include_str!("../../../examples/library/v2-ad-01.json");
+
+    fn v1_page() -> Value {
+        serde_json::json!([{
+            "require": [["ScheduledServerJS", "handle", null, [{
+                "markup": [["u_0_1", {"__html": "<div>Play chess</div>"}, 1, "HTML"]],
+                "deeplinkAdCard": {
+                    "adArchiveID": "123456789",
+                    "snapshot": {
+                        "title": "Chessiverse",
+                        "link_url": "https://chessiverse.com/",
+                        "creation_time": 1_700_000_000,
+                        "page_id": "157817344084965",
+                        "page_name": "Chessiverse",
+                        "page_profile_picture_url": "https://scontent.xx.fbcdn.net/profile.jpg",
+                        "page_entity_type": "regular_page",
+                        "page_is_profile_page": false,
+                        "page_like_count": 100,
+                        "instagram_url": "",
+                        "instagram_handle": "",
+                        "instagram_actor_name": "",
+                        "instagram_profile_pic_url": "",
+                        "videos": []
+                    }
+                }
+            }]]]
+        }])
+    }
+
+    #[test]
+    fn detect_formats() -> Result<(), Box<dyn std::error::Error>> {
+        let v2_value: Value = serde_json::from_str(AD_01_EXAMPLE)?;
+        let v2_response = LibraryResponse::extract(&v2_value)?;
+
+        assert_eq!(v2_response.format(), Some(Format::V2));
+        assert_eq!(
+            v2_response.ad().map(|ad| (ad.ad_id, ad.format)),
+            Some((576_535_441_426_103, Format::V2))
+        );
+
+        let v1_value = v1_page();
+        let v1_response = LibraryResponse::extract(&v1_value)?;
+
+        assert_eq!(v1_response.format(), Some(Format::V1));
+        assert_eq!(
+            v1_response.ads(),
+            vec![AdSummary {
+                format: Format::V1,
+                ad_id: 123_456_789,
+                page_id: 157_817_344_084_965,
+                page_name: "Chessiverse",
+                link_url: Some("https://chessiverse.com/"),
+            }]
+        );
+
+        let empty = serde_json::json!([{"require": []}]);
+
+        assert_eq!(LibraryResponse::extract(&empty)?, LibraryResponse::Empty);
+
+        // V1 markup without its snapshot is an error rather than an empty page.
+        let mut partial = v1_page();
+        partial[0]["require"][0][3][0]
+            .as_object_mut()
+            .ok_or("missing define")?
+            .remove("deeplinkAdCard");
+
+        assert!(matches!(
+            LibraryResponse::extract(&partial),
+            Err(Error::V1(v1::Error::MissingSnapshotElement))
+        ));
+        assert_eq!(
+            LibraryResponse::extract(&serde_json::json!([]))?.format(),
+            None
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! Structured interpretation of the Graph API's free-text target location names.
+
+use super::country::{Country, CountryCode};
+use super::{LocationType, TargetLocation};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// A target location name (e.g. `Mitte, Berlin, Germany`) split into its administrative levels.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LocationHierarchy<'a> {
+    pub neighborhood: Option<&'a str>,
+    pub city: Option<&'a str>,
+    pub county: Option<&'a str>,
+    pub region: Option<&'a str>,
+    /// Missing for country groups (e.g. `Europe`).
+    pub country: Option<Country<'a>>,
+}
+
+impl<'a> LocationHierarchy<'a> {
+    /// Parse a location name, using the location type to identify the most specific level.
+    #[must_use]
+    pub fn parse(name: &'a str, location_type: &LocationType) -> Self {
+        let mut hierarchy = Self::default();
+
+        if *location_type == LocationType::CountryGroups {
+            return hierarchy;
+        }
+
+        let mut parts = name
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+
+        hierarchy.country = parts.pop().map(parse_country);
+
+        if parts.is_empty() {
+            return hierarchy;
+        }
+
+        let first = parts.remove(0);
+
+        match location_type {
+            LocationType::Neighborhood => {
+                hierarchy.neighborhood = Some(first);
+
+                if !parts.is_empty() {
+                    hierarchy.city = Some(parts.remove(0));
+                }
+            }
+            LocationType::City | LocationType::MultiCity => {
+                hierarchy.city = Some(first);
+            }
+            LocationType::County => {
+                hierarchy.county = Some(first);
+            }
+            _ => {
+                hierarchy.region = Some(first);
+            }
+        }
+
+        // Any remaining parts sit between the named location and the country.
+        if hierarchy.region.is_none() {
+            hierarchy.region = parts.pop();
+        }
+
+        if hierarchy.county.is_none() && hierarchy.region.is_some() {
+            hierarchy.county = parts.pop();
+        }
+
+        hierarchy
+    }
+
+    #[must_use]
+    pub fn country_code(&self) -> Option<CountryCode> {
+        self.country.as_ref().and_then(Country::code)
+    }
+}
+
+fn parse_country(name: &str) -> Country<'_> {
+    CountryCode::from_name(name)
+        .or_else(|| name.parse().ok())
+        .map_or(Country::Unknown(Cow::Borrowed(name)), Country::Known)
+}
+
+impl TargetLocation<'_> {
+    #[must_use]
+    pub fn hierarchy(&self) -> LocationHierarchy<'_> {
+        LocationHierarchy::parse(&self.name, &self.location_type)
+    }
+}
+
+/// The targeting of an ad within a single country.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CountryTargeting<'a> {
+    pub included: Vec<&'a TargetLocation<'a>>,
+    pub excluded: Vec<&'a TargetLocation<'a>>,
+}
+
+impl CountryTargeting<'_> {
+    /// Whether the country as a whole is targeted (possibly with sub-national exclusions).
+    #[must_use]
+    pub fn includes_whole_country(&self) -> bool {
+        self.included
+            .iter()
+            .any(|location| location.location_type == LocationType::Countries)
+    }
+
+    /// Whether the country as a whole is excluded.
+    #[must_use]
+    pub fn excludes_whole_country(&self) -> bool {
+        self.excluded
+            .iter()
+            .any(|location| location.location_type == LocationType::Countries)
+    }
+}
+
+/// Inclusion and exclusion targeting grouped by country.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TargetingSummary<'a> {
+    pub countries: BTreeMap<Country<'a>, CountryTargeting<'a>>,
+    /// Locations that aren't tied to a single country (e.g. country groups).
+    pub other: Vec<&'a TargetLocation<'a>>,
+}
+
+impl<'a> TargetingSummary<'a> {
+    #[must_use]
+    pub fn new(locations: &'a [TargetLocation<'a>]) -> Self {
+        let mut summary = Self::default();
+
+        for location in locations {
+            match location.hierarchy().country {
+                Some(country) => {
+                    let targeting = summary.countries.entry(country).or_default();
+
+                    if location.excluded {
+                        targeting.excluded.push(location);
+                    } else {
+                        targeting.included.push(location);
+                    }
+                }
+                None => summary.other.push(location),
+            }
+        }
+
+        summary
+    }
+
+    /// Countries with at least one included location.
+    pub fn included_countries(&self) -> impl Iterator<Item = &Country<'a>> {
+        self.countries
+            .iter()
+            .filter(|(_, targeting)| !targeting.included.is_empty())
+            .map(|(country, _)| country)
+    }
+
+    /// Countries with at least one excluded location.
+    pub fn excluded_countries(&self) -> impl Iterator<Item = &Country<'a>> {
+        self.countries
+            .iter()
+            .filter(|(_, targeting)| !targeting.excluded.is_empty())
+            .map(|(country, _)| country)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(name: &str, location_type: LocationType, excluded: bool) -> TargetLocation<'_> {
+        TargetLocation {
+            name: Cow::Borrowed(name),
+            num_obfuscated: 0,
+            location_type,
+            excluded,
+        }
+    }
+
+    #[test]
+    fn parse_hierarchy_levels() {
+        let germany: CountryCode = "DE".parse().unwrap();
+
+        assert_eq!(
+            LocationHierarchy::parse("Germany", &LocationType::Countries),
+            LocationHierarchy {
+                country: Some(Country::Known(germany)),
+                ..LocationHierarchy::default()
+            }
+        );
+        assert_eq!(
+            LocationHierarchy::parse("Bavaria, Germany", &LocationType::Regions),
+            LocationHierarchy {
+                region: Some("Bavaria"),
+                country: Some(Country::Known(germany)),
+                ..LocationHierarchy::default()
+            }
+        );
+        assert_eq!(
+            LocationHierarchy::parse("Mitte, Berlin, Germany", &LocationType::Neighborhood),
+            LocationHierarchy {
+                neighborhood: Some("Mitte"),
+                city: Some("Berlin"),
+                country: Some(Country::Known(germany)),
+                ..LocationHierarchy::default()
+            }
+        );
+        assert_eq!(
+            LocationHierarchy::parse(
+                "Los Angeles County, California, United States",
+                &LocationType::County
+            ),
+            LocationHierarchy {
+                county: Some("Los Angeles County"),
+                region: Some("California"),
+                country: Some(Country::Known("US".parse().unwrap())),
+                ..LocationHierarchy::default()
+            }
+        );
+        assert_eq!(
+            LocationHierarchy::parse("Europe", &LocationType::CountryGroups),
+            LocationHierarchy::default()
+        );
+    }
+
+    #[test]
+    fn parse_unknown_country() {
+        assert_eq!(
+            LocationHierarchy::parse("Springfield, Freedonia", &LocationType::City).country,
+            Some(Country::Unknown("Freedonia".into()))
+        );
+    }
+
+    #[test]
+    fn summarize_by_country() {
+        let locations = vec![
+            location("Germany", LocationType::Countries, false),
+            location("Bavaria, Germany", LocationType::Regions, true),
+            location("Vienna, Austria", LocationType::City, false),
+            location("Europe", LocationType::CountryGroups, false),
+        ];
+
+        let summary = TargetingSummary::new(&locations);
+        let germany = &summary.countries[&Country::Known("DE".parse().unwrap())];
+
+        assert!(germany.includes_whole_country());
+        assert_eq!(germany.excluded[0].name, "Bavaria, Germany");
+        assert_eq!(
+            summary
+                .included_countries()
+                .map(Country::as_str)
+                .collect::<Vec<_>>(),
+            vec!["AT", "DE"]
+        );
+        assert_eq!(summary.excluded_countries().count(), 1);
+        assert_eq!(summary.other.len(), 1);
+    }
+}
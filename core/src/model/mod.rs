@@ -1,10 +1,26 @@
 use bounded_static_derive_more::ToStatic;
 use chrono::NaiveDate;
 use scraper_trail::archive::Archiveable;
-use serde_field_attributes::{integer_str, optional_integer_str, optional_integer_str_array};
+use serde_field_attributes::{
+    integer_str, optional_integer_str, optional_integer_str_array, represented_as_str,
+};
 use std::borrow::Cow;
 
+pub mod breakdown;
+mod code;
+pub mod country;
+pub mod language;
 pub mod library;
+pub mod location;
+pub mod reach;
+pub mod snapshot;
+
+/// A string that borrows from the input when it can.
+///
+/// Serde copies `Cow<'a, str>` values unless the field is marked `#[serde(borrow)]`, which only
+/// applies to fields whose type is exactly `Cow`, so models wrap this to borrow elsewhere.
+#[derive(serde::Deserialize)]
+pub(crate) struct BorrowedStr<'a>(#[serde(borrow)] pub(crate) Cow<'a, str>);
 
 #[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields, untagged)]
@@ -89,7 +105,8 @@ pub struct Ad<'a> {
     #[serde(with = "integer_str")]
     pub page_id: u64,
     pub page_name: Cow<'a, str>,
-    pub ad_snapshot_url: Cow<'a, str>,
+    #[serde(with = "represented_as_str")]
+    pub ad_snapshot_url: snapshot::SnapshotUrl,
     pub ad_creation_time: NaiveDate,
     pub ad_delivery_start_time: NaiveDate,
     pub ad_delivery_stop_time: Option<NaiveDate>,
@@ -100,7 +117,7 @@ pub struct Ad<'a> {
     pub age_country_gender_reach_breakdown: Option<Vec<CountryAgeGenderBreakdowns<'a>>>,
     pub beneficiary_payers: Option<Vec<BeneficiaryPayer<'a>>>,
     pub eu_total_reach: Option<usize>,
-    pub languages: Option<Vec<Cow<'a, str>>>,
+    pub languages: Option<Vec<language::Language<'a>>>,
     pub publisher_platforms: Option<Vec<PublisherPlatforms>>,
     #[serde(with = "optional_integer_str_array", default)]
     pub target_ages: Option<Vec<usize>>,
@@ -117,6 +134,56 @@ pub struct Ad<'a> {
     pub estimated_audience_size: Option<Bounds>,
 }
 
+impl Ad<'_> {
+    /// The age, country, and gender reach breakdown, flattened for aggregation.
+    #[must_use]
+    pub fn reach_breakdown(&self) -> breakdown::Breakdown<'_> {
+        breakdown::Breakdown::new(
+            self.age_country_gender_reach_breakdown
+                .as_deref()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// The breakdown total compared to `eu_total_reach`, if the ad has both.
+    #[must_use]
+    pub fn reach_comparison(&self) -> Option<breakdown::ReachComparison> {
+        self.age_country_gender_reach_breakdown.as_ref()?;
+
+        self.eu_total_reach
+            .map(|eu_total_reach| self.reach_breakdown().compare(eu_total_reach))
+    }
+
+    /// Total reach keyed by country.
+    #[must_use]
+    pub fn reach_by_location(&self) -> reach::ReachByLocation<'_> {
+        reach::ReachByLocation::new(self.total_reach_by_location.as_deref().unwrap_or_default())
+    }
+
+    /// Check the reach by location against `eu_total_reach` and the reach breakdown.
+    #[must_use]
+    pub fn reach_consistency(&self) -> reach::ReachConsistency<'_> {
+        self.reach_by_location()
+            .check(self.eu_total_reach, &self.reach_breakdown())
+    }
+
+    /// Inclusion and exclusion targeting grouped by country.
+    #[must_use]
+    pub fn targeting_summary(&self) -> location::TargetingSummary<'_> {
+        location::TargetingSummary::new(self.target_locations.as_deref().unwrap_or_default())
+    }
+
+    /// Whether the ad is listed as using the given language.
+    #[must_use]
+    pub fn has_language(&self, code: language::LanguageCode) -> bool {
+        self.languages.as_ref().is_some_and(|languages| {
+            languages
+                .iter()
+                .any(|language| language.code() == Some(code))
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Bounds {
@@ -162,6 +229,22 @@ pub enum AgeRange {
     Unknown,
 }
 
+impl AgeRange {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Range13_17 => "13-17",
+            Self::Range18_24 => "18-24",
+            Self::Range25_34 => "25-34",
+            Self::Range35_44 => "35-44",
+            Self::Range45_54 => "45-54",
+            Self::Range55_64 => "55-64",
+            Self::Range65 => "65+",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, ToStatic, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct BeneficiaryPayer<'a> {
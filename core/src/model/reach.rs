@@ -0,0 +1,255 @@
+//! Typed access to the Graph API's `total_reach_by_location` field.
+
+use super::KeyValue;
+use super::breakdown::Breakdown;
+use super::country::{Country, CountryCode};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Total reach keyed by country.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReachByLocation<'a> {
+    pub countries: BTreeMap<Country<'a>, usize>,
+    /// Locations listed without a value.
+    pub missing: Vec<Country<'a>>,
+}
+
+impl<'a> ReachByLocation<'a> {
+    #[must_use]
+    pub fn new(values: &'a [KeyValue<Cow<'a, str>, usize>]) -> Self {
+        let mut reach = Self::default();
+
+        for KeyValue { key, value } in values {
+            let country = Country::from(Cow::Borrowed(key.as_ref()));
+
+            match value {
+                Some(value) => {
+                    *reach.countries.entry(country).or_default() += value;
+                }
+                None => reach.missing.push(country),
+            }
+        }
+
+        reach
+    }
+
+    #[must_use]
+    pub fn get(&self, code: CountryCode) -> Option<usize> {
+        self.countries.get(&Country::Known(code)).copied()
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.countries.values().sum()
+    }
+
+    /// The countries with the highest reach, in descending order (ties broken by country).
+    #[must_use]
+    pub fn top(&self, n: usize) -> Vec<(&Country<'a>, usize)> {
+        let mut countries = self
+            .countries
+            .iter()
+            .map(|(country, reach)| (country, *reach))
+            .collect::<Vec<_>>();
+
+        countries.sort_by(|(country_a, reach_a), (country_b, reach_b)| {
+            reach_b.cmp(reach_a).then_with(|| country_a.cmp(country_b))
+        });
+        countries.truncate(n);
+        countries
+    }
+
+    /// The country's fraction of the total reach (if the total is non-zero).
+    #[must_use]
+    pub fn share(&self, code: CountryCode) -> Option<f64> {
+        let total = self.total();
+
+        (total > 0).then(|| share(self.get(code).unwrap_or_default(), total))
+    }
+
+    /// Every country's fraction of the total reach.
+    #[must_use]
+    pub fn shares(&self) -> BTreeMap<&Country<'a>, f64> {
+        let total = self.total();
+
+        if total == 0 {
+            BTreeMap::new()
+        } else {
+            self.countries
+                .iter()
+                .map(|(country, reach)| (country, share(*reach, total)))
+                .collect()
+        }
+    }
+
+    /// Compare against the reported EU total and the per-country breakdown totals.
+    ///
+    /// Nothing is compared if there is no location data.
+    #[must_use]
+    pub fn check(
+        &self,
+        eu_total_reach: Option<usize>,
+        breakdown: &Breakdown<'a>,
+    ) -> ReachConsistency<'a> {
+        if self.countries.is_empty() {
+            return ReachConsistency {
+                location_total: None,
+                eu_total_reach,
+                breakdown_mismatches: vec![],
+            };
+        }
+
+        let breakdown_mismatches = breakdown
+            .by_country()
+            .into_iter()
+            .filter_map(|(country, breakdown_reach)| {
+                let location_reach = self.countries.get(&country).copied();
+
+                (location_reach != Some(breakdown_reach)).then_some(CountryMismatch {
+                    country,
+                    location_reach,
+                    breakdown_reach,
+                })
+            })
+            .collect();
+
+        ReachConsistency {
+            location_total: Some(self.total()),
+            eu_total_reach,
+            breakdown_mismatches,
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn share(reach: usize, total: usize) -> f64 {
+    reach as f64 / total as f64
+}
+
+/// A country whose location reach differs from its age and gender breakdown total.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CountryMismatch<'a> {
+    pub country: Country<'a>,
+    pub location_reach: Option<usize>,
+    pub breakdown_reach: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReachConsistency<'a> {
+    /// The total reach across locations (missing if there is no location data).
+    pub location_total: Option<usize>,
+    pub eu_total_reach: Option<usize>,
+    pub breakdown_mismatches: Vec<CountryMismatch<'a>>,
+}
+
+impl ReachConsistency<'_> {
+    /// Whether the location total matches `eu_total_reach` (if present) and every breakdown total
+    /// (unknown if there is no location data).
+    #[must_use]
+    pub fn is_consistent(&self) -> Option<bool> {
+        self.location_total.map(|location_total| {
+            self.eu_total_reach
+                .is_none_or(|eu_total_reach| eu_total_reach == location_total)
+                && self.breakdown_mismatches.is_empty()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AgeGenderBreakdown, AgeRange, CountryAgeGenderBreakdowns};
+
+    fn example() -> Vec<KeyValue<Cow<'static, str>, usize>> {
+        vec![
+            KeyValue {
+                key: "DE".into(),
+                value: Some(300),
+            },
+            KeyValue {
+                key: "FR".into(),
+                value: Some(100),
+            },
+            KeyValue {
+                key: "AT".into(),
+                value: Some(100),
+            },
+            KeyValue {
+                key: "IT".into(),
+                value: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn typed_lookup() {
+        let values = example();
+        let reach = ReachByLocation::new(&values);
+
+        assert_eq!(reach.get("DE".parse().unwrap()), Some(300));
+        assert_eq!(reach.get("IT".parse().unwrap()), None);
+        assert_eq!(reach.missing, vec![Country::Known("IT".parse().unwrap())]);
+        assert_eq!(reach.total(), 500);
+    }
+
+    #[test]
+    fn top_and_shares() {
+        let values = example();
+        let reach = ReachByLocation::new(&values);
+
+        assert_eq!(
+            reach
+                .top(2)
+                .into_iter()
+                .map(|(country, reach)| (country.as_str(), reach))
+                .collect::<Vec<_>>(),
+            vec![("DE", 300), ("AT", 100)]
+        );
+        assert!((reach.share("FR".parse().unwrap()).unwrap() - 0.2).abs() < f64::EPSILON);
+        assert!((reach.shares().values().sum::<f64>() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn consistency() {
+        let values = example();
+        let reach = ReachByLocation::new(&values);
+        let breakdowns = vec![CountryAgeGenderBreakdowns {
+            country: "DE".into(),
+            age_gender_breakdowns: vec![AgeGenderBreakdown {
+                age_range: AgeRange::Range18_24,
+                female: Some(150),
+                male: Some(140),
+                unknown: None,
+            }],
+        }];
+
+        let consistency = reach.check(Some(500), &Breakdown::new(&breakdowns));
+
+        assert_eq!(consistency.is_consistent(), Some(false));
+        assert_eq!(
+            consistency.breakdown_mismatches,
+            vec![CountryMismatch {
+                country: Country::Known("DE".parse().unwrap()),
+                location_reach: Some(300),
+                breakdown_reach: 290,
+            }]
+        );
+        assert_eq!(
+            reach
+                .check(Some(500), &Breakdown::default())
+                .is_consistent(),
+            Some(true)
+        );
+        assert_eq!(
+            reach
+                .check(Some(400), &Breakdown::default())
+                .is_consistent(),
+            Some(false)
+        );
+
+        let unknown = ReachByLocation::default().check(Some(500), &Breakdown::new(&breakdowns));
+
+        assert_eq!(unknown.location_total, None);
+        assert_eq!(unknown.is_consistent(), None);
+    }
+}
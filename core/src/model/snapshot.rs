@@ -0,0 +1,267 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+const DOMAIN: &str = "www.facebook.com";
+const PATH: &str = "/ads/archive/render_ad/";
+const REDACTED: &str = "REDACTED";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The input (with any access token redacted).
+    #[error("Invalid snapshot URL")]
+    InvalidSnapshotUrl(String),
+}
+
+/// The Graph API's `ad_snapshot_url`, parsed if it has the expected shape.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SnapshotUrl {
+    Ref(SnapshotRef),
+    /// A URL in an unexpected shape (e.g. another host), with any access token redacted.
+    Other(String),
+}
+
+impl SnapshotUrl {
+    #[must_use]
+    pub const fn snapshot_ref(&self) -> Option<&SnapshotRef> {
+        match self {
+            Self::Ref(snapshot_ref) => Some(snapshot_ref),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl Display for SnapshotUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ref(snapshot_ref) => snapshot_ref.fmt(f),
+            Self::Other(url) => f.write_str(url),
+        }
+    }
+}
+
+impl FromStr for SnapshotUrl {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse().map_or_else(|_| Self::Other(redact(s)), Self::Ref))
+    }
+}
+
+/// Replace the values of any `access_token` query parameters.
+fn redact(s: &str) -> String {
+    let Some((base, query)) = s.split_once('?') else {
+        return s.to_string();
+    };
+
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some(("access_token", _)) => format!("access_token={REDACTED}"),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{base}?{query}")
+}
+
+/// A reference to an ad's rendered snapshot, parsed from the Graph API's `ad_snapshot_url`.
+///
+/// The access token embedded in the original URL is never retained, and the serialized form
+/// replaces it with a placeholder.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SnapshotRef {
+    pub ad_id: u64,
+    pub access_token: Option<RedactedToken>,
+}
+
+/// Marker for an access token that was present in a snapshot URL but has been discarded.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RedactedToken;
+
+impl SnapshotRef {
+    #[must_use]
+    pub const fn new(ad_id: u64) -> Self {
+        Self {
+            ad_id,
+            access_token: None,
+        }
+    }
+
+    #[must_use]
+    pub fn parse_url(url: &url::Url) -> Option<Self> {
+        if url.scheme() == "https" && url.domain() == Some(DOMAIN) && url.path() == PATH {
+            let mut ad_id = None;
+            let mut access_token = None;
+
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "id" => {
+                        ad_id = Some(value.parse().ok()?);
+                    }
+                    "access_token" => {
+                        access_token = Some(RedactedToken);
+                    }
+                    _ => {}
+                }
+            }
+
+            ad_id.map(|ad_id| Self {
+                ad_id,
+                access_token,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for SnapshotRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "https://{DOMAIN}{PATH}?id={}", self.ad_id)?;
+
+        if self.access_token.is_some() {
+            write!(f, "&access_token={REDACTED}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SnapshotRef {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .and_then(|url| Self::parse_url(&url))
+            .ok_or_else(|| Error::InvalidSnapshotUrl(redact(s)))
+    }
+}
+
+impl From<SnapshotRef> for crate::library::request::Params {
+    fn from(value: SnapshotRef) -> Self {
+        Self::new(value.ad_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_typical_url() {
+        let snapshot_ref = "https://www.facebook.com/ads/archive/render_ad/?id=576535441426103&access_token=EAAB123"
+            .parse::<SnapshotRef>()
+            .unwrap();
+
+        assert_eq!(snapshot_ref.ad_id, 576_535_441_426_103);
+        assert_eq!(snapshot_ref.access_token, Some(RedactedToken));
+    }
+
+    #[test]
+    fn display_redacts_token() {
+        let snapshot_ref =
+            "https://www.facebook.com/ads/archive/render_ad/?id=123&access_token=EAAB123"
+                .parse::<SnapshotRef>()
+                .unwrap();
+
+        assert_eq!(
+            snapshot_ref.to_string(),
+            "https://www.facebook.com/ads/archive/render_ad/?id=123&access_token=REDACTED"
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let snapshot_ref =
+            "https://www.facebook.com/ads/archive/render_ad/?id=123&access_token=EAAB123"
+                .parse::<SnapshotRef>()
+                .unwrap();
+
+        assert_eq!(
+            snapshot_ref.to_string().parse::<SnapshotRef>().unwrap(),
+            snapshot_ref
+        );
+    }
+
+    #[test]
+    fn parse_without_token() {
+        assert_eq!(
+            "https://www.facebook.com/ads/archive/render_ad/?id=123"
+                .parse::<SnapshotRef>()
+                .unwrap(),
+            SnapshotRef::new(123)
+        );
+    }
+
+    #[test]
+    fn parse_missing_id() {
+        assert!(
+            "https://www.facebook.com/ads/archive/render_ad/?access_token=EAAB123"
+                .parse::<SnapshotRef>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_non_numeric_id() {
+        assert!(
+            "https://www.facebook.com/ads/archive/render_ad/?id=abc"
+                .parse::<SnapshotRef>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_wrong_path() {
+        assert!(
+            "https://www.facebook.com/ads/library/?id=123"
+                .parse::<SnapshotRef>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn error_redacts_token() {
+        let err = "http://www.facebook.com/ads/archive/render_ad/?id=123&access_token=EAAB123"
+            .parse::<SnapshotRef>()
+            .unwrap_err();
+
+        assert!(matches!(
+            &err,
+            Error::InvalidSnapshotUrl(s)
+                if s == "http://www.facebook.com/ads/archive/render_ad/?id=123&access_token=REDACTED"
+        ));
+        assert!(!format!("{err:?}").contains("EAAB123"));
+    }
+
+    #[test]
+    fn snapshot_url_fallback() {
+        assert_eq!(
+            "https://www.facebook.com/ads/archive/render_ad/?id=123&access_token=EAAB123"
+                .parse::<SnapshotUrl>()
+                .unwrap()
+                .snapshot_ref(),
+            Some(&SnapshotRef {
+                ad_id: 123,
+                access_token: Some(RedactedToken),
+            })
+        );
+        assert_eq!(
+            "https://m.facebook.com/ads/archive/render_ad/?access_token=EAAB123&id=123"
+                .parse::<SnapshotUrl>()
+                .unwrap(),
+            SnapshotUrl::Other(
+                "https://m.facebook.com/ads/archive/render_ad/?access_token=REDACTED&id=123"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn into_library_params() {
+        let params: crate::library::request::Params = SnapshotRef::new(123).into();
+        assert_eq!(params.ad_id, 123);
+    }
+}